@@ -0,0 +1,860 @@
+use super::config::Config;
+use super::controller::Application;
+use super::core::format::ScheduleFormat;
+use super::core::history::{History, Status};
+use super::core::ical;
+use super::core::occurrence::Occurrence;
+use super::core::schedule::Schedule;
+use super::core::task;
+use super::instance::InstanceLock;
+#[cfg(unix)]
+use super::ipc::{self, Request};
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const USAGE: &str = "\
+Usage: wesche [--config <path>] [command]
+
+Commands:
+  run                   start the tray app (the default)
+  daemon                run headless, without the tray or the viewer
+  today                 list today's tasks
+  week                  list the tasks of the next 7 days
+  history [YYYY-MM-DD]  show what was recorded for a day; today by default
+  next                  show the next task to start
+  validate <file>       check a schedule file
+  export [--format json|yaml|toml|txt|ics] [--output <file>]
+                        write the schedule
+  import <file.ics> [--output <file>]
+                        convert an iCalendar file into a schedule
+  convert <file> [--format json|yaml|toml|txt] [--output <file>]
+                        rewrite a schedule file in another format
+  mark done|skipped <task-id> [YYYY-MM-DD]
+                        record a task's status in the history while no
+                        instance is running; today by default
+  notify-test           send a test notification through the configured backends
+  ctl <request>         control the running instance: status, reload, open-viewer,
+                        snooze [minutes], done, skip, dnd [minutes] (60 by
+                        default), resume (ends dnd), upcoming [count] or quit
+  help                  show this message";
+
+#[cfg(unix)]
+const DEFAULT_DO_NOT_DISTURB_MINUTES: u32 = 60;
+
+// how far ahead `next` looks
+const NEXT_TASK_SEARCH_DAYS: i64 = 7;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Schedule(ScheduleFormat),
+    Ical,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ics" | "ical" | "icalendar" => Ok(ExportFormat::Ical),
+            _ => s
+                .parse()
+                .map(ExportFormat::Schedule)
+                .map_err(|_| format!("unknown export format \"{}\"", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Run,
+    Daemon,
+    Today,
+    Week,
+    History(Option<NaiveDate>),
+    Next,
+    Validate(PathBuf),
+    Export {
+        format: ExportFormat,
+        output_path: Option<PathBuf>,
+    },
+    Import {
+        input_path: PathBuf,
+        output_path: Option<PathBuf>,
+    },
+    Convert {
+        input_path: PathBuf,
+        format: ScheduleFormat,
+        output_path: Option<PathBuf>,
+    },
+    Mark {
+        status: Status,
+        task_id: String,
+        date: Option<NaiveDate>,
+    },
+    NotifyTest,
+    #[cfg(unix)]
+    Control(Request),
+    Help,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Arguments {
+    config_path: Option<PathBuf>,
+    command: Command,
+}
+
+impl Arguments {
+    // `args` without the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Arguments, String> {
+        let mut config_path = None;
+        let mut is_help = false;
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => {
+                    (name.to_string(), Some(value.to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+
+            match name.as_str() {
+                "--help" | "-h" => is_help = true,
+                "--config" | "--format" | "--output" => {
+                    let value = value
+                        .or_else(|| args.next())
+                        .ok_or_else(|| format!("{} needs a value", name))?;
+                    if name == "--config" {
+                        config_path = Some(PathBuf::from(value));
+                    } else {
+                        options.push((name, value));
+                    }
+                }
+                _ if name.starts_with('-') => return Err(format!("unknown option \"{}\"", arg)),
+                _ => positional.push(arg),
+            }
+        }
+
+        if is_help {
+            return Ok(Arguments {
+                config_path,
+                command: Command::Help,
+            });
+        }
+
+        let mut positional = positional.into_iter();
+        let command = match positional.next().as_deref() {
+            None | Some("run") => Command::Run,
+            Some("daemon") => Command::Daemon,
+            Some("today") => Command::Today,
+            Some("week") => Command::Week,
+            Some("history") => Command::History(parse_date(positional.next())?),
+            Some("next") => Command::Next,
+            Some("validate") => Command::Validate(
+                positional
+                    .next()
+                    .map(PathBuf::from)
+                    .ok_or("validate needs a schedule file")?,
+            ),
+            Some("export") => {
+                let output_path = take_option(&mut options, "--output").map(PathBuf::from);
+                let format = match take_option(&mut options, "--format") {
+                    Some(format) => format.parse()?,
+                    None => get_extension(output_path.as_ref())
+                        .and_then(|extension| extension.parse().ok())
+                        .unwrap_or(ExportFormat::Schedule(ScheduleFormat::Json)),
+                };
+                Command::Export {
+                    format,
+                    output_path,
+                }
+            }
+            Some("import") => Command::Import {
+                input_path: positional
+                    .next()
+                    .map(PathBuf::from)
+                    .ok_or("import needs an iCalendar file")?,
+                output_path: take_option(&mut options, "--output").map(PathBuf::from),
+            },
+            Some("convert") => {
+                let input_path = positional
+                    .next()
+                    .map(PathBuf::from)
+                    .ok_or("convert needs a schedule file")?;
+                let output_path = take_option(&mut options, "--output").map(PathBuf::from);
+                let format = match take_option(&mut options, "--format") {
+                    Some(format) => format.parse()?,
+                    None => get_extension(output_path.as_ref())
+                        .and_then(|extension| extension.parse().ok())
+                        .unwrap_or(ScheduleFormat::Json),
+                };
+                Command::Convert {
+                    input_path,
+                    format,
+                    output_path,
+                }
+            }
+            Some("mark") => {
+                let status = match positional.next().as_deref() {
+                    Some("done") => Status::Done,
+                    Some("skipped") | Some("skip") => Status::Skipped,
+                    Some(status) => return Err(format!("unknown status \"{}\"", status)),
+                    None => return Err("mark needs a status".to_string()),
+                };
+                let task_id = positional.next().ok_or("mark needs a task id")?;
+                let date = parse_date(positional.next())?;
+                Command::Mark {
+                    status,
+                    task_id,
+                    date,
+                }
+            }
+            Some("notify-test") => Command::NotifyTest,
+            #[cfg(unix)]
+            Some("ctl") => Command::Control(parse_request(&mut positional)?),
+            Some("help") => Command::Help,
+            Some(command) => return Err(format!("unknown command \"{}\"", command)),
+        };
+
+        if let Some(arg) = positional.next() {
+            return Err(format!("unexpected argument \"{}\"", arg));
+        }
+        if let Some((name, _)) = options.first() {
+            return Err(format!("{} doesn't apply to this command", name));
+        }
+
+        Ok(Arguments {
+            config_path,
+            command,
+        })
+    }
+
+    pub fn get_config_path(&self) -> Option<&PathBuf> {
+        self.config_path.as_ref()
+    }
+
+    pub fn get_command(&self) -> &Command {
+        &self.command
+    }
+}
+
+// removes every `name` option; the last one wins
+fn take_option(options: &mut Vec<(String, String)>, name: &str) -> Option<String> {
+    let mut value = None;
+    options.retain(|(option, option_value)| {
+        if option == name {
+            value = Some(option_value.clone());
+        }
+        option != name
+    });
+
+    value
+}
+
+fn parse_date(date: Option<String>) -> Result<Option<NaiveDate>, String> {
+    date.map(|date| {
+        date.parse::<NaiveDate>()
+            .map_err(|_| format!("invalid date \"{}\"", date))
+    })
+    .transpose()
+}
+
+fn get_extension(file_path: Option<&PathBuf>) -> Option<&str> {
+    file_path?.extension()?.to_str()
+}
+
+// `ctl` arguments after the request name are all optional numbers
+#[cfg(unix)]
+fn parse_request(args: &mut impl Iterator<Item = String>) -> Result<Request, String> {
+    let name = args.next().ok_or("ctl needs a request")?;
+    let mut number = || {
+        args.next()
+            .map(|arg| {
+                arg.parse::<u32>()
+                    .map_err(|_| format!("invalid number \"{}\"", arg))
+            })
+            .transpose()
+    };
+
+    Ok(match name.as_str() {
+        "status" => Request::Status,
+        "reload" => Request::Reload,
+        "open-viewer" => Request::OpenViewer,
+        "snooze" => Request::Snooze {
+            minutes: number()?,
+            task: None,
+        },
+        "done" => Request::Done { task: None },
+        "skip" => Request::Skip { task: None },
+        "dnd" => Request::DoNotDisturb {
+            minutes: number()?.unwrap_or(DEFAULT_DO_NOT_DISTURB_MINUTES),
+        },
+        "resume" => Request::DoNotDisturbOff,
+        "upcoming" => Request::Upcoming {
+            limit: number()?.map(|limit| limit as usize),
+        },
+        "quit" => Request::Quit,
+        name => return Err(format!("unknown request \"{}\"", name)),
+    })
+}
+
+// runs every command except `run` and `daemon`, returning the exit code
+pub async fn execute(command: &Command, config: Config) -> std::io::Result<i32> {
+    match command {
+        Command::Run | Command::Daemon => unreachable!("the scheduler is started by main"),
+        Command::Help => println!("{}", USAGE),
+        Command::Validate(file_path) => return validate(file_path).await,
+        Command::Today => {
+            let application = load_application(config).await?;
+            let schedule = application.get_schedule().lock().await.clone();
+            let now = application.get_clock().now();
+
+            println!(
+                "{}",
+                format_day(&schedule, application.get_history(), now.date(), now)
+            );
+        }
+        Command::Week => {
+            let application = load_application(config).await?;
+            let schedule = application.get_schedule().lock().await.clone();
+            let now = application.get_clock().now();
+
+            let days = now
+                .date()
+                .iter_days()
+                .take(7)
+                .map(|date| format_day(&schedule, application.get_history(), date, now))
+                .collect::<Vec<_>>();
+            println!("{}", days.join("\n\n"));
+        }
+        Command::History(date) => {
+            let application = load_application(config).await?;
+            let schedule = application.get_schedule().lock().await.clone();
+            let date = date.unwrap_or(application.get_clock().now().date());
+
+            println!(
+                "{}",
+                format_history(&schedule, application.get_history(), date)
+            );
+        }
+        Command::Next => {
+            let application = load_application(config).await?;
+            let schedule = application.get_schedule().lock().await.clone();
+
+            println!("{}", format_next(&schedule, application.get_clock().now()));
+        }
+        Command::Export {
+            format,
+            output_path,
+        } => {
+            let application = load_application(config).await?;
+            let schedule = application.get_schedule().lock().await.clone();
+
+            let contents = export(&schedule, *format, application.get_clock().now())?;
+            write_output(&contents, output_path.as_ref()).await?;
+        }
+        Command::Import {
+            input_path,
+            output_path,
+        } => return import(input_path, output_path.as_ref()).await,
+        Command::Convert {
+            input_path,
+            format,
+            output_path,
+        } => return convert(input_path, *format, output_path.as_ref()).await,
+        Command::Mark {
+            status,
+            task_id,
+            date,
+        } => return mark(config, *status, task_id, *date).await,
+        Command::NotifyTest => {
+            if let Err(err) = Application::new(config).notify_test() {
+                eprintln!("{}", err);
+                return Ok(1);
+            }
+        }
+        #[cfg(unix)]
+        Command::Control(request) => {
+            let response = match ipc::send_request(config.get_socket_path(), request).await {
+                Ok(response) => response,
+                Err(err) => {
+                    eprintln!(
+                        "No running instance at {}: {}",
+                        config.get_socket_path().display(),
+                        err
+                    );
+                    return Ok(1);
+                }
+            };
+            println!("{}", serde_json::to_string_pretty(&response)?);
+            if !response.is_ok() {
+                return Ok(1);
+            }
+        }
+    }
+
+    Ok(0)
+}
+
+async fn load_application(config: Config) -> std::io::Result<Application> {
+    let mut application = Application::new(config);
+    application.load_schedule().await?;
+
+    Ok(application)
+}
+
+// warnings are printed but only errors fail the check
+async fn validate(file_path: &PathBuf) -> std::io::Result<i32> {
+    let contents = async_std::fs::read_to_string(file_path).await?;
+
+    let report = match read_format(file_path).parse(&contents) {
+        Ok(schedule) => schedule.validate(),
+        Err(report) => report,
+    };
+    for issue in report.get_issues() {
+        eprintln!("{}: {}", file_path.display(), issue);
+    }
+
+    if report.has_errors() {
+        Ok(1)
+    } else {
+        println!("{}: ok", file_path.display());
+        Ok(0)
+    }
+}
+
+// skipped events and schedule warnings are printed, and only errors fail
+async fn import(input_path: &PathBuf, output_path: Option<&PathBuf>) -> std::io::Result<i32> {
+    let contents = async_std::fs::read_to_string(input_path).await?;
+
+    let today = chrono::Local::now().date_naive();
+    let time_zone = iana_time_zone::get_timezone().unwrap_or_else(|_| "unknown".to_string());
+    let (schedule, report) =
+        match ical::import_calendar(&contents, today, &time_zone, &chrono::Local) {
+            Ok((schedule, report)) => (Some(schedule), report),
+            Err(report) => (None, report),
+        };
+    for issue in report.get_issues() {
+        eprintln!("{}: {}", input_path.display(), issue);
+    }
+    let Some(schedule) = schedule else {
+        return Ok(1);
+    };
+
+    // the JSON schedule `import` always wrote, unless the output names another format
+    let format = output_path
+        .and_then(|output_path| ScheduleFormat::from_path(output_path))
+        .unwrap_or(ScheduleFormat::Json);
+    let contents = format.write(&schedule).map_err(invalid_data)?;
+    write_output(&contents, output_path).await?;
+
+    Ok(0)
+}
+
+async fn convert(
+    input_path: &PathBuf,
+    format: ScheduleFormat,
+    output_path: Option<&PathBuf>,
+) -> std::io::Result<i32> {
+    let contents = async_std::fs::read_to_string(input_path).await?;
+
+    let schedule = match read_format(input_path).parse(&contents) {
+        Ok(schedule) => schedule,
+        Err(report) => {
+            for issue in report.get_issues() {
+                eprintln!("{}: {}", input_path.display(), issue);
+            }
+            return Ok(1);
+        }
+    };
+
+    let contents = format.write(&schedule).map_err(invalid_data)?;
+    write_output(&contents, output_path).await?;
+
+    Ok(0)
+}
+
+// edits the history file directly, so it holds the instance lock to keep a
+// running instance from overwriting the change
+async fn mark(
+    config: Config,
+    status: Status,
+    task_id: &str,
+    date: Option<NaiveDate>,
+) -> std::io::Result<i32> {
+    let Some(_instance_lock) = InstanceLock::acquire(&config.get_lock_path())? else {
+        eprintln!("wesche is running, use `ctl done` or `ctl skip` instead");
+        return Ok(1);
+    };
+
+    let history_path = config.get_history_path().to_path_buf();
+    let history_retention_days = config.get_history_retention_days();
+    let application = load_application(config).await?;
+    let schedule = application.get_schedule().lock().await.clone();
+    let now = application.get_clock().now();
+    let date = date.unwrap_or(now.date());
+
+    let Some(occurrence) = schedule
+        .get_occurrences_on(date)
+        .into_iter()
+        .find(|occurrence| occurrence.get_task().get_id() == task_id)
+    else {
+        eprintln!("No task \"{}\" on {}", task_id, date);
+        return Ok(1);
+    };
+
+    let mut history = match async_std::fs::read_to_string(&history_path).await {
+        Ok(contents) => serde_json::from_str::<History>(&contents)
+            .map_err(|err| invalid_data(format!("{}: {}", history_path.display(), err)))?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => History::new(),
+        Err(err) => return Err(err),
+    };
+    let is_pruned =
+        history_retention_days.is_some_and(|days| history.prune(now - Duration::days(days.into())));
+    if history.set_status(&occurrence, status, now) || is_pruned {
+        if let Some(directory) = history_path.parent() {
+            async_std::fs::create_dir_all(directory).await?;
+        }
+        async_std::fs::write(&history_path, serde_json::to_string_pretty(&history)?).await?;
+    }
+    println!("{}  [{}]", format_occurrence(&occurrence), status);
+
+    Ok(0)
+}
+
+// files without a known extension are read as JSON
+fn read_format(file_path: &Path) -> ScheduleFormat {
+    ScheduleFormat::from_path(file_path).unwrap_or(ScheduleFormat::Json)
+}
+
+async fn write_output(contents: &str, output_path: Option<&PathBuf>) -> std::io::Result<()> {
+    match output_path {
+        Some(output_path) => async_std::fs::write(output_path, contents).await,
+        None => {
+            print!("{}", contents);
+            Ok(())
+        }
+    }
+}
+
+fn invalid_data(err: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+}
+
+fn export(
+    schedule: &Schedule,
+    format: ExportFormat,
+    now: NaiveDateTime,
+) -> std::io::Result<String> {
+    match format {
+        ExportFormat::Schedule(format) => format.write(schedule).map_err(invalid_data),
+        ExportFormat::Ical => Ok(ical::export_schedule(
+            schedule,
+            now.date(),
+            Utc::now().naive_utc(),
+        )),
+    }
+}
+
+fn format_day(
+    schedule: &Schedule,
+    history: &History,
+    date: NaiveDate,
+    now: NaiveDateTime,
+) -> String {
+    let mut lines = vec![format!(
+        "{} {}",
+        schedule.get_day_of_week(date.weekday()),
+        date.format("%m/%d")
+    )];
+
+    let occurrences = schedule.get_occurrences_on(date);
+    if occurrences.is_empty() {
+        lines.push("  (no tasks)".to_string());
+    }
+    for occurrence in occurrences {
+        lines.push(format!(
+            "  {}  [{}]",
+            format_occurrence(&occurrence),
+            history.get_status(&occurrence, now)
+        ));
+    }
+
+    lines.join("\n")
+}
+
+fn format_next(schedule: &Schedule, now: NaiveDateTime) -> String {
+    let until = now + Duration::days(NEXT_TASK_SEARCH_DAYS);
+
+    match schedule.get_upcoming_occurrences(now, until).first() {
+        Some(occurrence) => format!(
+            "{} {}  (in {} min)",
+            occurrence.get_start().format("%m/%d"),
+            format_occurrence(occurrence),
+            (occurrence.get_start() - now).num_minutes()
+        ),
+        None => format!(
+            "Nothing scheduled in the next {} days",
+            NEXT_TASK_SEARCH_DAYS
+        ),
+    }
+}
+
+// the recorded entries, so tasks since removed from the schedule still show
+fn format_history(schedule: &Schedule, history: &History, date: NaiveDate) -> String {
+    let mut lines = vec![format!(
+        "{} {}",
+        schedule.get_day_of_week(date.weekday()),
+        date.format("%m/%d")
+    )];
+
+    let entries = history.get_entries_on(date);
+    if entries.is_empty() {
+        lines.push("  (nothing recorded)".to_string());
+    }
+    for entry in entries {
+        let end_time = task::format_time(&entry.get_end().time());
+        let end_time = match (entry.get_end().date() - date).num_days() {
+            0 => end_time,
+            end_day_offset => format!("{} (+{})", end_time, end_day_offset),
+        };
+        lines.push(format!(
+            "  {}-{}  {}  [{}]",
+            task::format_time(&entry.get_start().time()),
+            end_time,
+            entry.get_title(),
+            entry.get_status()
+        ));
+    }
+
+    lines.join("\n")
+}
+
+fn format_occurrence(occurrence: &Occurrence) -> String {
+    let task = occurrence.get_task();
+
+    format!(
+        "{}-{}  {}",
+        task::format_time(&task.get_start_time()),
+        task::format_end_time(task),
+        task.get_title()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Arguments, String> {
+        Arguments::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        assert_eq!(parse(&[]).unwrap().get_command(), &Command::Run);
+        assert_eq!(parse(&["daemon"]).unwrap().get_command(), &Command::Daemon);
+        assert_eq!(
+            parse(&["--config", "my.toml", "today"]).unwrap(),
+            Arguments {
+                config_path: Some(PathBuf::from("my.toml")),
+                command: Command::Today,
+            }
+        );
+        assert_eq!(
+            parse(&["validate", "schedule.json"]).unwrap().get_command(),
+            &Command::Validate(PathBuf::from("schedule.json"))
+        );
+        assert_eq!(
+            parse(&["export", "--format=json", "--output", "out.json"])
+                .unwrap()
+                .get_command(),
+            &Command::Export {
+                format: ExportFormat::Schedule(ScheduleFormat::Json),
+                output_path: Some(PathBuf::from("out.json")),
+            }
+        );
+        assert_eq!(
+            parse(&["import", "calendar.ics", "--output=schedule.json"])
+                .unwrap()
+                .get_command(),
+            &Command::Import {
+                input_path: PathBuf::from("calendar.ics"),
+                output_path: Some(PathBuf::from("schedule.json")),
+            }
+        );
+
+        assert_eq!(
+            parse(&["export", "--output", "week.ics"])
+                .unwrap()
+                .get_command(),
+            &Command::Export {
+                format: ExportFormat::Ical,
+                output_path: Some(PathBuf::from("week.ics")),
+            }
+        );
+        assert_eq!(
+            parse(&["convert", "schedule.json", "--output=schedule.yml"])
+                .unwrap()
+                .get_command(),
+            &Command::Convert {
+                input_path: PathBuf::from("schedule.json"),
+                format: ScheduleFormat::Yaml,
+                output_path: Some(PathBuf::from("schedule.yml")),
+            }
+        );
+        assert_eq!(
+            parse(&["convert", "schedule.yaml", "--format", "toml"])
+                .unwrap()
+                .get_command(),
+            &Command::Convert {
+                input_path: PathBuf::from("schedule.yaml"),
+                format: ScheduleFormat::Toml,
+                output_path: None,
+            }
+        );
+
+        #[cfg(unix)]
+        assert_eq!(
+            parse(&["ctl", "snooze", "10"]).unwrap().get_command(),
+            &Command::Control(Request::Snooze {
+                minutes: Some(10),
+                task: None
+            })
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            parse(&["ctl", "dnd"]).unwrap().get_command(),
+            &Command::Control(Request::DoNotDisturb { minutes: 60 })
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            parse(&["ctl", "resume"]).unwrap().get_command(),
+            &Command::Control(Request::DoNotDisturbOff)
+        );
+        #[cfg(unix)]
+        assert!(parse(&["ctl", "dnd", "soon"]).is_err());
+
+        assert_eq!(
+            parse(&["mark", "skip", "standup", "2026-10-19"])
+                .unwrap()
+                .get_command(),
+            &Command::Mark {
+                status: Status::Skipped,
+                task_id: "standup".to_string(),
+                date: NaiveDate::from_ymd_opt(2026, 10, 19),
+            }
+        );
+        assert!(parse(&["mark", "missed", "standup"]).is_err());
+        assert_eq!(
+            parse(&["history", "2026-10-19"]).unwrap().get_command(),
+            &Command::History(NaiveDate::from_ymd_opt(2026, 10, 19))
+        );
+        assert_eq!(
+            parse(&["history"]).unwrap().get_command(),
+            &Command::History(None)
+        );
+        assert!(parse(&["mark", "done", "standup", "monday"]).is_err());
+
+        assert!(parse(&["validate"]).is_err());
+        assert!(parse(&["export", "--format", "pdf"]).is_err());
+        assert!(parse(&["import", "calendar.ics", "--format", "json"]).is_err());
+        assert!(parse(&["today", "--output", "out.json"]).is_err());
+        assert!(parse(&["yesterday"]).is_err());
+    }
+
+    #[test]
+    fn test_format_day_and_next() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{ "days": [{ "day_of_week": "Monday", "tasks": [
+                { "title": "Standup", "start_time": "09:00", "end_time": "09:15", "details": "" },
+                { "title": "Night shift", "start_time": "23:00", "end_time": "07:00", "details": "" }
+            ] }] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let now = monday.and_hms_opt(10, 0, 0).unwrap();
+
+        assert_eq!(
+            format_day(&schedule, &History::new(), monday, now),
+            "Monday 10/19\n  09:00-09:15  Standup  [missed]\n  23:00-07:00 (+1)  Night shift  [pending]"
+        );
+        assert_eq!(
+            format_day(&schedule, &History::new(), monday.succ_opt().unwrap(), now),
+            "Tuesday 10/20\n  (no tasks)"
+        );
+        assert_eq!(
+            format_next(&schedule, now),
+            "10/19 23:00-07:00 (+1)  Night shift  (in 780 min)"
+        );
+    }
+
+    #[async_std::test]
+    async fn test_mark_without_running_instance() {
+        let directory = std::env::temp_dir().join(format!("wesche-mark-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(
+            directory.join("schedule.json"),
+            r#"{ "days": [{ "day_of_week": "Monday", "tasks": [
+                { "id": "standup", "title": "Standup", "start_time": "09:00", "end_time": "09:15", "details": "" }
+            ] }] }"#,
+        )
+        .unwrap();
+        let record = toml::from_str(
+            r#"
+            schedules = ["schedule.json"]
+            history = "history.json"
+            socket = "wesche.sock"
+            "#,
+        )
+        .unwrap();
+        let config = Config::from_record(record, &directory).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+
+        assert_eq!(
+            mark(config.clone(), Status::Done, "standup", Some(monday))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            mark(config.clone(), Status::Done, "review", Some(monday))
+                .await
+                .unwrap(),
+            1
+        );
+
+        let history = serde_json::from_str::<History>(
+            &std::fs::read_to_string(directory.join("history.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            format_history(&Schedule::new(), &history, monday),
+            "Monday 10/19\n  09:00-09:15  Standup  [done]"
+        );
+        assert_eq!(
+            format_history(&Schedule::new(), &history, monday.succ_opt().unwrap()),
+            "Tuesday 10/20\n  (nothing recorded)"
+        );
+        assert_eq!(
+            history.get_recorded_status(&(
+                "standup".to_string(),
+                monday.and_hms_opt(9, 0, 0).unwrap()
+            )),
+            Some(Status::Done)
+        );
+
+        let _instance_lock = InstanceLock::acquire(&config.get_lock_path()).unwrap();
+        assert_eq!(
+            mark(config, Status::Skipped, "standup", Some(monday))
+                .await
+                .unwrap(),
+            1
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,155 @@
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> NaiveDateTime;
+
+    // how long to really wait for `duration` to pass on this clock
+    fn to_real_duration(&self, duration: Duration) -> std::time::Duration;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+
+    fn to_real_duration(&self, duration: Duration) -> std::time::Duration {
+        duration.to_std().unwrap_or_default()
+    }
+}
+
+// stays at the given time until it is moved explicitly
+pub struct FixedClock {
+    date_time: Mutex<NaiveDateTime>,
+}
+
+impl FixedClock {
+    pub fn new(date_time: NaiveDateTime) -> FixedClock {
+        FixedClock {
+            date_time: Mutex::new(date_time),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn set(&self, date_time: NaiveDateTime) {
+        *self.date_time.lock().unwrap() = date_time;
+    }
+
+    #[cfg(test)]
+    pub fn advance(&self, duration: Duration) {
+        *self.date_time.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> NaiveDateTime {
+        *self.date_time.lock().unwrap()
+    }
+
+    fn to_real_duration(&self, duration: Duration) -> std::time::Duration {
+        duration.to_std().unwrap_or_default()
+    }
+}
+
+// runs from `origin` at `speed` times real time, e.g. to simulate a day
+pub struct AcceleratedClock {
+    origin: NaiveDateTime,
+    started_at: Instant,
+    speed: u32,
+}
+
+impl AcceleratedClock {
+    pub fn new(origin: NaiveDateTime, speed: u32) -> AcceleratedClock {
+        AcceleratedClock {
+            origin,
+            started_at: Instant::now(),
+            speed,
+        }
+    }
+
+    fn get_elapsed(&self, real_elapsed: std::time::Duration) -> Duration {
+        Duration::from_std(real_elapsed * self.speed).unwrap_or(Duration::max_value())
+    }
+}
+
+impl Clock for AcceleratedClock {
+    fn now(&self) -> NaiveDateTime {
+        self.origin + self.get_elapsed(self.started_at.elapsed())
+    }
+
+    fn to_real_duration(&self, duration: Duration) -> std::time::Duration {
+        duration.to_std().unwrap_or_default() / self.speed
+    }
+}
+
+// WESCHE_SIMULATE_FROM="2026-10-19 08:00" starts an accelerated clock at that
+// time, running WESCHE_SIMULATE_SPEED (default 60) times faster than real time;
+// a speed of 0 keeps the clock fixed there
+pub fn read_clock() -> Arc<dyn Clock> {
+    let Ok(origin) = env::var("WESCHE_SIMULATE_FROM") else {
+        return Arc::new(SystemClock);
+    };
+
+    let Some(origin) = parse_date_time(&origin) else {
+        dbg!("Invalid WESCHE_SIMULATE_FROM, using the system clock");
+        return Arc::new(SystemClock);
+    };
+    let speed = match env::var("WESCHE_SIMULATE_SPEED") {
+        Ok(speed) => speed.parse().unwrap_or_else(|err| {
+            dbg!(err);
+            DEFAULT_SIMULATION_SPEED
+        }),
+        Err(_) => DEFAULT_SIMULATION_SPEED,
+    };
+
+    match speed {
+        0 => Arc::new(FixedClock::new(origin)),
+        speed => Arc::new(AcceleratedClock::new(origin, speed)),
+    }
+}
+
+const DEFAULT_SIMULATION_SPEED: u32 = 60;
+
+fn parse_date_time(date_time: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(date_time, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(date_time, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_time(chrono::NaiveTime::MIN))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accelerated_clock() {
+        let origin = parse_date_time("2026-10-19 08:00").unwrap();
+        let clock = AcceleratedClock::new(origin, 60);
+
+        assert_eq!(
+            clock.get_elapsed(std::time::Duration::from_secs(2)),
+            Duration::minutes(2)
+        );
+        assert_eq!(
+            clock.to_real_duration(Duration::minutes(30)),
+            std::time::Duration::from_secs(30)
+        );
+        assert!(clock.now() >= origin);
+    }
+
+    #[test]
+    fn test_fixed_clock() {
+        let clock = FixedClock::new(parse_date_time("2026-10-22").unwrap());
+
+        clock.advance(Duration::minutes(17 * 60 + 29));
+        assert_eq!(clock.now(), parse_date_time("2026-10-22 17:29").unwrap());
+    }
+}
@@ -0,0 +1,504 @@
+use super::core::day_of_week::Language;
+use super::core::notification::CatchUpPolicy;
+use super::core::quiet_hours::QuietHours;
+
+use serde::Deserialize;
+use std::env;
+use std::path::{Path, PathBuf};
+
+const CONFIG_DIR_NAME: &str = "wesche";
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+// defaults in dev and test builds, relative to the project root
+const SCHEDULE_FILE_PATH: &str = if cfg!(test) {
+    "assets/tests/schedule.json"
+} else {
+    "assets/schedule.json"
+};
+
+const HISTORY_FILE_PATH: &str = if cfg!(test) {
+    "target/tests/history.json"
+} else {
+    "assets/history.json"
+};
+
+const ICON_FILE_PATH: &str = "assets/icon.ico";
+
+// bundled, so that an installed binary has an icon outside a checkout
+const ICON: &[u8] = include_bytes!("../../assets/icon.ico");
+
+const DEFAULT_MAX_SLEEP_SECONDS: u32 = 60;
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigRecord {
+    #[serde(default)]
+    schedules: Option<Vec<String>>,
+    #[serde(default)]
+    icon: Option<String>,
+    #[serde(default)]
+    history: Option<String>,
+    #[serde(default)]
+    history_retention_days: Option<u32>,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    socket: Option<String>,
+    #[serde(default)]
+    notifications: NotificationsRecord,
+    #[serde(default)]
+    polling: PollingRecord,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationsRecord {
+    #[serde(default)]
+    backends: Option<Vec<String>>,
+    #[serde(default)]
+    command: Option<String>,
+    #[serde(default)]
+    catch_up: Option<String>,
+    #[serde(default)]
+    quiet_hours: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct PollingRecord {
+    #[serde(default)]
+    max_sleep_seconds: Option<u32>,
+    #[serde(default)]
+    watch_schedule: Option<bool>,
+}
+
+// every setting resolved, with paths made absolute
+#[derive(Clone, Debug)]
+pub struct Config {
+    schedule_paths: Vec<PathBuf>,
+    icon_path: PathBuf,
+    history_path: PathBuf,
+    // `None` keeps every entry
+    history_retention_days: Option<u32>,
+    language: Option<Language>,
+    socket_path: PathBuf,
+    notifiers: Vec<String>,
+    notify_command: String,
+    catch_up_policy: CatchUpPolicy,
+    quiet_hours: Option<QuietHours>,
+    max_sleep_seconds: u32,
+    watch_schedule: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            schedule_paths: vec![get_default_schedule_path()],
+            icon_path: get_default_icon_path(),
+            history_path: get_default_history_path(),
+            history_retention_days: None,
+            language: None,
+            socket_path: get_default_socket_path(),
+            notifiers: vec!["desktop".to_string()],
+            notify_command: "".to_string(),
+            catch_up_policy: CatchUpPolicy::default(),
+            quiet_hours: None,
+            max_sleep_seconds: DEFAULT_MAX_SLEEP_SECONDS,
+            watch_schedule: true,
+        }
+    }
+}
+
+impl Config {
+    // relative paths in the record are resolved against `base_path`, the
+    // directory of the config file; settings it leaves out keep their defaults
+    pub fn from_record(record: ConfigRecord, base_path: &Path) -> Result<Config, Vec<String>> {
+        let mut config = Config::default();
+        let mut errors = Vec::new();
+
+        if let Some(schedules) = record.schedules {
+            if schedules.is_empty() {
+                errors.push("schedules: at least one schedule file is required".to_string());
+            }
+            config.schedule_paths = schedules
+                .iter()
+                .map(|schedule| resolve_path(base_path, schedule))
+                .collect();
+        }
+        if let Some(icon) = record.icon {
+            config.icon_path = resolve_path(base_path, &icon);
+        }
+        if let Some(history) = record.history {
+            config.history_path = resolve_path(base_path, &history);
+        }
+        match record.history_retention_days {
+            Some(0) => errors.push("history_retention_days: must be at least 1".to_string()),
+            Some(days) => config.history_retention_days = Some(days),
+            None => (),
+        }
+        if let Some(language) = record.language {
+            match language.parse() {
+                Ok(language) => config.language = Some(language),
+                Err(err) => errors.push(format!("language: {}", err)),
+            }
+        }
+        if let Some(socket) = record.socket {
+            config.socket_path = resolve_path(base_path, &socket);
+        }
+
+        let notifications = record.notifications;
+        if let Some(backends) = notifications.backends {
+            config.notifiers = backends;
+        }
+        if let Some(command) = notifications.command {
+            config.notify_command = command;
+        }
+        if let Some(catch_up) = notifications.catch_up {
+            match catch_up.parse() {
+                Ok(catch_up_policy) => config.catch_up_policy = catch_up_policy,
+                Err(err) => errors.push(format!("notifications.catch_up: {}", err)),
+            }
+        }
+        if let Some(quiet_hours) = notifications.quiet_hours {
+            match quiet_hours.parse() {
+                Ok(quiet_hours) => config.quiet_hours = Some(quiet_hours),
+                Err(err) => errors.push(format!("notifications.quiet_hours: {}", err)),
+            }
+        }
+
+        let polling = record.polling;
+        match polling.max_sleep_seconds {
+            Some(0) => errors.push("polling.max_sleep_seconds: must be at least 1".to_string()),
+            Some(max_sleep_seconds) => config.max_sleep_seconds = max_sleep_seconds,
+            None => (),
+        }
+        if let Some(watch_schedule) = polling.watch_schedule {
+            config.watch_schedule = watch_schedule;
+        }
+
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors)
+        }
+    }
+
+    // the config file given with --config, else the first one found in the
+    // XDG config directories, else the defaults; WESCHE_* variables override
+    // either
+    pub fn load(config_path: Option<&Path>) -> Result<Config, String> {
+        let config_path = match config_path {
+            Some(config_path) => Some(config_path.to_path_buf()),
+            None => find_config_path(),
+        };
+
+        let mut config = match config_path {
+            Some(config_path) => {
+                if cfg!(debug_assertions) {
+                    dbg!(&config_path);
+                }
+                let contents = std::fs::read_to_string(&config_path)
+                    .map_err(|err| format!("{}: {}", config_path.display(), err))?;
+                let record = toml::from_str::<ConfigRecord>(&contents)
+                    .map_err(|err| format!("{}: {}", config_path.display(), err))?;
+                let base_path = config_path.parent().unwrap_or(Path::new(""));
+
+                Config::from_record(record, base_path)
+                    .map_err(|errors| format!("{}: {}", config_path.display(), errors.join("; ")))?
+            }
+            None => Config::default(),
+        };
+        config.apply_env();
+
+        Ok(config)
+    }
+
+    // WESCHE_LANGUAGE, WESCHE_NOTIFIERS="desktop,stdout,command",
+    // WESCHE_NOTIFY_COMMAND, WESCHE_CATCH_UP and WESCHE_QUIET_HOURS="22:30-06:00"
+    fn apply_env(&mut self) {
+        if let Ok(language) = env::var("WESCHE_LANGUAGE") {
+            match language.parse() {
+                Ok(language) => self.language = Some(language),
+                Err(err) => {
+                    dbg!(err);
+                }
+            }
+        }
+        if let Ok(notifiers) = env::var("WESCHE_NOTIFIERS") {
+            self.notifiers = notifiers.split(',').map(str::to_string).collect();
+        }
+        if let Ok(command) = env::var("WESCHE_NOTIFY_COMMAND") {
+            self.notify_command = command;
+        }
+        if let Ok(catch_up) = env::var("WESCHE_CATCH_UP") {
+            match catch_up.parse() {
+                Ok(catch_up_policy) => self.catch_up_policy = catch_up_policy,
+                Err(err) => {
+                    dbg!(err);
+                }
+            }
+        }
+        if let Ok(quiet_hours) = env::var("WESCHE_QUIET_HOURS") {
+            match quiet_hours.parse() {
+                Ok(quiet_hours) => self.quiet_hours = Some(quiet_hours),
+                Err(err) => {
+                    dbg!(err);
+                }
+            }
+        }
+    }
+
+    pub fn get_schedule_paths(&self) -> &Vec<PathBuf> {
+        self.schedule_paths.as_ref()
+    }
+
+    pub fn get_icon_path(&self) -> &Path {
+        self.icon_path.as_path()
+    }
+
+    // writes the bundled icon to the default icon path if nothing is there;
+    // a configured icon is left alone
+    pub fn install_default_icon(&self) -> std::io::Result<()> {
+        if self.icon_path.exists() || self.icon_path != get_default_icon_path() {
+            return Ok(());
+        }
+        if let Some(directory) = self.icon_path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        std::fs::write(&self.icon_path, ICON)
+    }
+
+    pub fn get_history_path(&self) -> &Path {
+        self.history_path.as_path()
+    }
+
+    pub fn get_history_retention_days(&self) -> Option<u32> {
+        self.history_retention_days
+    }
+
+    pub fn get_language(&self) -> Option<Language> {
+        self.language
+    }
+
+    pub fn get_socket_path(&self) -> &Path {
+        self.socket_path.as_path()
+    }
+
+    // next to the socket, so that instances sharing a socket share the lock
+    pub fn get_lock_path(&self) -> PathBuf {
+        self.socket_path.with_extension("lock")
+    }
+
+    pub fn get_notifiers(&self) -> &Vec<String> {
+        self.notifiers.as_ref()
+    }
+
+    pub fn get_notify_command(&self) -> &str {
+        self.notify_command.as_str()
+    }
+
+    pub fn get_catch_up_policy(&self) -> CatchUpPolicy {
+        self.catch_up_policy
+    }
+
+    pub fn get_quiet_hours(&self) -> Option<QuietHours> {
+        self.quiet_hours
+    }
+
+    pub fn get_max_sleep_seconds(&self) -> u32 {
+        self.max_sleep_seconds
+    }
+
+    pub fn should_watch_schedule(&self) -> bool {
+        self.watch_schedule
+    }
+}
+
+fn find_config_path() -> Option<PathBuf> {
+    get_config_dirs()
+        .into_iter()
+        .map(|config_dir| config_dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+        .find(|config_path| config_path.is_file())
+}
+
+// $XDG_CONFIG_HOME (or the platform's equivalent) first, then $XDG_CONFIG_DIRS
+fn get_config_dirs() -> Vec<PathBuf> {
+    let mut config_dirs = dirs_next::config_dir().into_iter().collect::<Vec<_>>();
+
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        let system_dirs = env::var("XDG_CONFIG_DIRS")
+            .ok()
+            .filter(|system_dirs| !system_dirs.is_empty())
+            .unwrap_or_else(|| "/etc/xdg".to_string());
+        config_dirs.extend(
+            system_dirs
+                .split(':')
+                .filter(|system_dir| !system_dir.is_empty())
+                .map(PathBuf::from),
+        );
+    }
+
+    config_dirs
+}
+
+// $XDG_CONFIG_HOME/wesche/schedule.json, or the project's assets in dev and
+// test builds
+fn get_default_schedule_path() -> PathBuf {
+    match dirs_next::config_dir() {
+        Some(config_path) if !cfg!(debug_assertions) => {
+            config_path.join(CONFIG_DIR_NAME).join("schedule.json")
+        }
+        _ => read_project_root_path().join(SCHEDULE_FILE_PATH),
+    }
+}
+
+// $XDG_DATA_HOME/wesche/history.json, or next to the schedule in dev and test
+// builds
+fn get_default_history_path() -> PathBuf {
+    match dirs_next::data_dir() {
+        Some(data_path) if !cfg!(debug_assertions) => {
+            data_path.join(CONFIG_DIR_NAME).join("history.json")
+        }
+        _ => read_project_root_path().join(HISTORY_FILE_PATH),
+    }
+}
+
+// $XDG_DATA_HOME/wesche/icon.ico, or the project's assets in dev and test
+// builds
+fn get_default_icon_path() -> PathBuf {
+    match dirs_next::data_dir() {
+        Some(data_path) if !cfg!(debug_assertions) => {
+            data_path.join(CONFIG_DIR_NAME).join("icon.ico")
+        }
+        _ => read_project_root_path().join(ICON_FILE_PATH),
+    }
+}
+
+// $XDG_RUNTIME_DIR/wesche.sock, or one per user in the temporary directory
+fn get_default_socket_path() -> PathBuf {
+    match dirs_next::runtime_dir() {
+        Some(runtime_path) => runtime_path.join("wesche.sock"),
+        None => env::temp_dir().join(format!(
+            "wesche-{}.sock",
+            env::var("USER").unwrap_or_default()
+        )),
+    }
+}
+
+fn resolve_path(base_path: &Path, path: &str) -> PathBuf {
+    let path = match path.strip_prefix("~/") {
+        Some(relative_path) => match dirs_next::home_dir() {
+            Some(home_path) => home_path.join(relative_path),
+            None => PathBuf::from(path),
+        },
+        None => PathBuf::from(path),
+    };
+
+    base_path.join(path)
+}
+
+fn read_project_root_path() -> PathBuf {
+    if let Ok(project_root_path) = env::var("PROJECT_ROOT") {
+        if cfg!(debug_assertions) {
+            dbg!(project_root_path.as_str());
+        }
+        PathBuf::from(project_root_path)
+    } else {
+        if cfg!(debug_assertions) {
+            dbg!("Project root is not set");
+        }
+        PathBuf::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_from_record() {
+        let record = toml::from_str::<ConfigRecord>(
+            r#"
+            schedules = ["schedule.json", "/srv/wesche/shared.json"]
+            history = "state/history.json"
+            history_retention_days = 365
+            language = "ja"
+
+            [notifications]
+            backends = ["desktop", "command"]
+            command = "notify-send -u low"
+            catch_up = "replay_all"
+            quiet_hours = "22:30-06:00"
+
+            [polling]
+            max_sleep_seconds = 30
+            watch_schedule = false
+            "#,
+        )
+        .unwrap();
+        let config = Config::from_record(record, Path::new("/home/me/.config/wesche")).unwrap();
+
+        assert_eq!(
+            config.get_schedule_paths(),
+            &vec![
+                PathBuf::from("/home/me/.config/wesche/schedule.json"),
+                PathBuf::from("/srv/wesche/shared.json"),
+            ]
+        );
+        assert_eq!(
+            config.get_history_path(),
+            Path::new("/home/me/.config/wesche/state/history.json")
+        );
+        assert_eq!(config.get_history_retention_days(), Some(365));
+        assert_eq!(config.get_language(), Some(Language::Japanese));
+        assert_eq!(config.get_notifiers(), &vec!["desktop", "command"]);
+        assert_eq!(config.get_catch_up_policy(), CatchUpPolicy::ReplayAll);
+        assert_eq!(
+            config.get_quiet_hours(),
+            Some("22:30-06:00".parse().unwrap())
+        );
+        assert_eq!(config.get_max_sleep_seconds(), 30);
+        assert!(!config.should_watch_schedule());
+    }
+
+    #[test]
+    fn test_config_file_keeps_default_paths() {
+        let record = toml::from_str::<ConfigRecord>(r#"language = "en""#).unwrap();
+        let config = Config::from_record(record, Path::new("/home/me/.config/wesche")).unwrap();
+        let default_config = Config::default();
+
+        assert_eq!(
+            config.get_schedule_paths(),
+            default_config.get_schedule_paths()
+        );
+        assert_eq!(config.get_history_path(), default_config.get_history_path());
+        assert_eq!(config.get_history_retention_days(), None);
+    }
+
+    #[test]
+    fn test_invalid_config_names_every_setting() {
+        let record = toml::from_str::<ConfigRecord>(
+            r#"
+            language = "klingon"
+
+            [notifications]
+            quiet_hours = "late"
+
+            [polling]
+            max_sleep_seconds = 0
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Config::from_record(record, Path::new("")).unwrap_err(),
+            vec![
+                "language: unknown language \"klingon\"",
+                "notifications.quiet_hours: invalid quiet hours \"late\" (expected HH:MM-HH:MM)",
+                "polling.max_sleep_seconds: must be at least 1",
+            ]
+        );
+        assert!(toml::from_str::<ConfigRecord>("schedule = \"typo.json\"").is_err());
+    }
+}
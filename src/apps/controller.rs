@@ -1,96 +1,131 @@
+use super::clock::{self, Clock};
+use super::config::Config;
+use super::core::format::ScheduleFormat;
+use super::core::history::{History, OccurrenceKey, Status};
+use super::core::notification::{CatchUpPolicy, NotificationKind, PlannedNotification};
+use super::core::occurrence::Occurrence;
+use super::core::quiet_hours::QuietHours;
 use super::core::schedule;
 use super::core::schedule::Schedule;
 use super::core::task;
+use super::core::validation::{Severity, ValidationReport};
+use super::notifier::{self, Notifier};
 use super::view::window;
 
+pub use super::core::notification::{NotificationAction, TASK_ACTIONS};
+
+pub const DO_NOT_DISTURB_MINUTES: [u32; 3] = [30, 60, 120];
+
 use async_std::channel::{Receiver, Sender};
 use async_std::fs::File;
 use async_std::path::Path;
 use async_std::prelude::*;
 use async_std::sync::Mutex;
-use chrono::{Datelike, Local};
-use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
-use notify_rust::Notification;
+use chrono::NaiveDateTime;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::{env, thread};
-
-const SCHEDULE_FILE_PATH: &str = if cfg!(test) {
-    "assets/tests/schedule.json"
-} else {
-    "assets/schedule.json"
-};
+use std::thread;
+
+// wakes the scheduler loop; the flags on `Application` stay the source of
+// truth, so an event dropped because one is already pending loses nothing
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ControllerEvent {
+    ScheduleChanged,
+    // an action without a target applies to the last task notification
+    Action(Option<OccurrenceKey>, NotificationAction),
+    // minutes of do-not-disturb from now, or `None` to end it
+    DoNotDisturb(Option<u32>),
+    Shutdown,
+}
 
-const ICON_FILE_PATH: &str = "assets/icon.ico";
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Delivery {
+    Show,
+    Missed,
+    Hold,
+}
 
-fn read_project_root_path() -> String {
-    if let Ok(project_root_path) = env::var("PROJECT_ROOT") {
-        if cfg!(debug_assertions) {
-            dbg!(project_root_path.as_str());
-        }
-        project_root_path
-    } else {
-        if cfg!(debug_assertions) {
-            dbg!("Project root is not set");
-        }
-        "".to_string()
-    }
+struct DueNotification {
+    summary: String,
+    body: String,
+    target: Option<OccurrenceKey>,
+    is_silent: bool,
 }
 
 struct FileObserver {
-    changed_sender: Sender<String>,
-    pub changed_receiver: Receiver<String>,
-    file_path: String,
+    changed_sender: Sender<ControllerEvent>,
+    file_paths: Vec<PathBuf>,
+}
+
+struct NotificationState {
+    occurrence_range: (NaiveDateTime, NaiveDateTime),
+    is_finished: bool,
 }
 
 pub struct Application {
+    config: Config,
     file_observer: FileObserver,
     schedule: Arc<Mutex<schedule::Schedule>>,
-    finished_task_map: std::collections::HashMap<usize, AtomicBool>,
+    clock: Arc<dyn Clock>,
+    notifier: Arc<dyn Notifier>,
+    // by occurrence, as a recurring task can be in the window more than once
+    finished_task_map: HashMap<(OccurrenceKey, NotificationKind), NotificationState>,
+    snoozed_map: HashMap<OccurrenceKey, NaiveDateTime>,
+    history: History,
+    history_file_path: String,
+    catch_up_policy: CatchUpPolicy,
+    quiet_hours: Option<QuietHours>,
+    do_not_disturb_until: Option<NaiveDateTime>,
+    digest_queue: Vec<DueNotification>,
+    last_checked_at: Option<NaiveDateTime>,
+    last_notified_occurrence: Option<OccurrenceKey>,
+    event_sender: Sender<ControllerEvent>,
+    event_receiver: Receiver<ControllerEvent>,
+    viewer_sender: Sender<()>,
+    viewer_receiver: Receiver<()>,
     is_shutdown: AtomicBool,
     is_opened_viewer: AtomicBool,
+    has_viewer: bool,
 }
 
 impl FileObserver {
-    pub fn new() -> FileObserver {
-        let (changed_sender, changed_receiver) = async_std::channel::bounded(1);
-
+    pub fn new(changed_sender: Sender<ControllerEvent>) -> FileObserver {
         FileObserver {
             changed_sender,
-            changed_receiver,
-            file_path: "".to_string(),
+            file_paths: Vec::new(),
         }
     }
 
-    pub fn set_file_path(&mut self, file_path: &str) {
-        self.file_path = file_path.to_string();
-    }
-
-    pub fn get_file_path(&self) -> &str {
-        self.file_path.as_str()
+    pub fn set_file_paths(&mut self, file_paths: &[PathBuf]) {
+        self.file_paths = file_paths.to_vec();
     }
 
     pub async fn observe_file(&self) -> std::io::Result<()> {
-        let file_path = Arc::new(std::path::PathBuf::from(&self.file_path));
-        let file_path_buf = file_path.to_path_buf();
+        let file_paths = self.file_paths.clone();
+        let watched_paths = file_paths.clone();
         let tx = self.changed_sender.clone();
 
         thread::spawn(move || {
             let mut watcher = RecommendedWatcher::new(
                 move |res: notify::Result<Event>| {
                     if let Ok(event) = res {
-                        if event.paths.contains(&file_path_buf) {
-                            let _ = tx.try_send("".to_string());
+                        if event.paths.iter().any(|path| watched_paths.contains(path)) {
+                            let _ = tx.try_send(ControllerEvent::ScheduleChanged);
                         }
                     }
                 },
-                Config::default(),
+                notify::Config::default(),
             )
             .unwrap();
 
-            watcher
-                .watch(&file_path, RecursiveMode::NonRecursive)
-                .unwrap();
+            for file_path in file_paths.iter() {
+                watcher
+                    .watch(file_path, RecursiveMode::NonRecursive)
+                    .unwrap();
+            }
 
             loop {
                 std::thread::park();
@@ -102,13 +137,44 @@ impl FileObserver {
 }
 
 impl Application {
-    pub fn new() -> Application {
+    pub fn new(config: Config) -> Application {
+        let notifier =
+            notifier::build_notifier(config.get_notifiers(), config.get_notify_command());
+
+        Application::with_backends(config, clock::read_clock(), notifier)
+    }
+
+    pub fn with_backends(
+        config: Config,
+        clock: Arc<dyn Clock>,
+        notifier: Arc<dyn Notifier>,
+    ) -> Application {
+        let (event_sender, event_receiver) = async_std::channel::bounded(EVENT_QUEUE_SIZE);
+        let (viewer_sender, viewer_receiver) = async_std::channel::bounded(1);
+
         Application {
-            file_observer: FileObserver::new(),
+            catch_up_policy: config.get_catch_up_policy(),
+            quiet_hours: config.get_quiet_hours(),
+            config,
+            file_observer: FileObserver::new(event_sender.clone()),
             schedule: Arc::new(Mutex::new(Schedule::new())),
-            finished_task_map: std::collections::HashMap::new(),
+            clock,
+            notifier,
+            finished_task_map: HashMap::new(),
+            snoozed_map: HashMap::new(),
+            history: History::new(),
+            history_file_path: "".to_string(),
+            do_not_disturb_until: None,
+            digest_queue: Vec::new(),
+            last_checked_at: None,
+            last_notified_occurrence: None,
+            event_sender,
+            event_receiver,
+            viewer_sender,
+            viewer_receiver,
             is_shutdown: AtomicBool::new(false),
             is_opened_viewer: AtomicBool::new(false),
+            has_viewer: false,
         }
     }
 
@@ -116,24 +182,131 @@ impl Application {
         self.schedule.clone()
     }
 
-    pub async fn load_schedule(&mut self) -> std::io::Result<()> {
-        let project_root_path = read_project_root_path();
+    pub fn get_clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
 
-        let file_path = Path::new(&project_root_path).join(SCHEDULE_FILE_PATH);
+    pub async fn load_schedule(&mut self) -> std::io::Result<()> {
         self.file_observer
-            .set_file_path(file_path.to_str().unwrap());
-        let mut file = File::open(file_path).await?;
+            .set_file_paths(self.config.get_schedule_paths());
 
-        let mut contents = String::new();
+        let schedule = match self.read_schedule().await {
+            Ok(Ok((schedule, report))) => {
+                self.notify_warnings(&report);
+                schedule
+            }
+            Ok(Err(report)) => {
+                dbg!("Failed to load the schedule");
+                dbg!(report.to_string());
+                self.notify_report("⚠ Failed to load the schedule", &report);
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    report.to_string(),
+                ));
+            }
+            Err(err) => {
+                dbg!("Failed to load the schedule");
+                dbg!(&err);
+                self.show_notification("⚠ Failed to load the schedule", &err.to_string());
+                return Err(err);
+            }
+        };
+        *self.schedule.lock().await = schedule;
 
-        file.read_to_string(&mut contents).await?;
+        let history_path = self.config.get_history_path().to_path_buf();
+        self.load_history(history_path.to_str().unwrap()).await;
 
-        {
-            let mut schedule = self.schedule.lock().await;
-            *schedule = serde_json::from_str(&contents)?;
+        Ok(())
+    }
+
+    // every schedule file merged in the configured order, with its warnings;
+    // the outer error is for a file that can't be read, the inner one for a
+    // schedule that is invalid
+    async fn read_schedule(
+        &self,
+    ) -> std::io::Result<Result<(Schedule, ValidationReport), ValidationReport>> {
+        let mut schedule: Option<Schedule> = None;
+
+        for file_path in self.config.get_schedule_paths() {
+            let mut file = File::open(file_path).await?;
+            let mut contents = String::new();
+            file.read_to_string(&mut contents).await?;
+            if cfg!(debug_assertions) {
+                dbg!(&contents);
+            }
+
+            let format = ScheduleFormat::from_path(file_path).unwrap_or(ScheduleFormat::Json);
+            let parsed = match format.parse(&contents) {
+                Ok(parsed) => parsed,
+                Err(report) => return Ok(Err(report)),
+            };
+            match schedule.as_mut() {
+                Some(schedule) => schedule.merge(parsed),
+                None => schedule = Some(parsed),
+            }
         }
 
-        Ok(())
+        let mut schedule = schedule.unwrap_or_else(Schedule::new);
+        let report = schedule.validate();
+        if report.has_errors() {
+            return Ok(Err(report));
+        }
+        if let Some(language) = self.config.get_language() {
+            schedule.set_display_language(language);
+        }
+
+        Ok(Ok((schedule, report)))
+    }
+
+    // a history file that can't be read is left alone rather than overwritten
+    pub async fn load_history(&mut self, file_path: &str) {
+        match async_std::fs::read_to_string(file_path).await {
+            Ok(contents) => match serde_json::from_str::<History>(&contents) {
+                Ok(history) => {
+                    self.history = history;
+                    self.history_file_path = file_path.to_string();
+                }
+                Err(err) => {
+                    dbg!("Failed to parse the history file, not recording history");
+                    dbg!(err);
+                }
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                self.history = History::new();
+                self.history_file_path = file_path.to_string();
+            }
+            Err(err) => {
+                dbg!("Failed to read the history file, not recording history");
+                dbg!(err);
+            }
+        }
+    }
+
+    pub fn get_history(&self) -> &History {
+        &self.history
+    }
+
+    // entries older than the configured retention are dropped first
+    async fn save_history(&mut self) {
+        if self.history_file_path.is_empty() {
+            return;
+        }
+        if let Some(days) = self.config.get_history_retention_days() {
+            self.history
+                .prune(self.clock.now() - chrono::Duration::days(days.into()));
+        }
+
+        let result = async {
+            if let Some(directory) = Path::new(&self.history_file_path).parent() {
+                async_std::fs::create_dir_all(directory).await?;
+            }
+            let contents = serde_json::to_string_pretty(&self.history)?;
+            async_std::fs::write(&self.history_file_path, contents).await
+        }
+        .await;
+        if let Err(err) = result {
+            dbg!(err);
+        }
     }
 
     pub async fn start_observer(&mut self) -> std::io::Result<()> {
@@ -146,14 +319,26 @@ impl Application {
 
     pub fn shutdown(&mut self) {
         self.is_shutdown.store(true, Ordering::Relaxed);
+        let _ = self.event_sender.try_send(ControllerEvent::Shutdown);
+        let _ = self.viewer_sender.try_send(());
     }
 
     pub fn check_opened_viewer(&self) -> bool {
         self.is_opened_viewer.load(Ordering::Relaxed)
     }
 
+    // false when running headless, or without a system tray
+    pub fn has_viewer(&self) -> bool {
+        self.has_viewer
+    }
+
+    pub fn set_has_viewer(&mut self, has_viewer: bool) {
+        self.has_viewer = has_viewer;
+    }
+
     pub fn open_viewer(&mut self) {
         self.is_opened_viewer.store(true, Ordering::Relaxed);
+        let _ = self.viewer_sender.try_send(());
     }
 
     pub fn close_viewer(&mut self) {
@@ -161,123 +346,570 @@ impl Application {
     }
 
     pub fn get_icon_file_path(&self) -> String {
-        Path::new(&read_project_root_path())
-            .join(ICON_FILE_PATH)
+        if let Err(err) = self.config.install_default_icon() {
+            dbg!(err);
+        }
+        self.config
+            .get_icon_path()
             .to_str()
             .expect("Failed to convert path to string")
             .to_string()
     }
 
-    pub async fn update_contents(&mut self) {
-        match async_std::future::timeout(
-            std::time::Duration::from_millis(NOTIFICATION_CHECK_INTERVAL.into()),
-            self.file_observer.changed_receiver.recv(),
+    pub fn get_event_sender(&self) -> Sender<ControllerEvent> {
+        self.event_sender.clone()
+    }
+
+    pub fn get_event_receiver(&self) -> Receiver<ControllerEvent> {
+        self.event_receiver.clone()
+    }
+
+    pub fn notify_test(&self) -> Result<(), String> {
+        self.notifier.notify(
+            "🔔 Test notification",
+            &format!("Sent through: {}", self.config.get_notifiers().join(", ")),
         )
-        .await
+    }
+
+    pub fn supports_notification_actions(&self) -> bool {
+        self.notifier.supports_actions()
+    }
+
+    // from the tray menu when notifications can't carry buttons, or from the
+    // control socket
+    pub fn request_action(&self, target: Option<OccurrenceKey>, action: NotificationAction) {
+        if let Err(err) = self
+            .event_sender
+            .try_send(ControllerEvent::Action(target, action))
         {
-            Ok(Ok(_)) => {
-                if let Ok(contents) =
-                    async_std::fs::read_to_string(self.file_observer.get_file_path()).await
-                {
-                    if cfg!(debug_assertions) {
-                        dbg!(&contents);
-                    }
-                    let mut schedule = self.schedule.lock().await;
-                    *schedule = match serde_json::from_str(&contents) {
-                        Ok(contents) => contents,
-                        _ => {
-                            dbg!("Failed to parse schedule file");
-                            Schedule::new()
-                        }
-                    };
-                }
+            dbg!(err);
+        }
+    }
+
+    pub async fn handle_action(
+        &mut self,
+        target: Option<OccurrenceKey>,
+        action: NotificationAction,
+    ) {
+        let Some(target) = target.or_else(|| self.last_notified_occurrence.clone()) else {
+            dbg!("No task notification to apply the action to");
+            return;
+        };
+        if cfg!(debug_assertions) {
+            dbg!(&target, action);
+        }
+
+        let status = match action {
+            NotificationAction::Snooze { minutes } => {
+                let until = self.clock.now() + chrono::Duration::minutes(minutes.into());
+                self.snoozed_map.insert(target, until);
+                return;
             }
-            Ok(Err(_)) => {
-                dbg!("Failed to receive file change event");
-                std::process::exit(-1);
+            NotificationAction::Done => Status::Done,
+            NotificationAction::Skip => Status::Skipped,
+        };
+
+        let schedule = self.schedule.clone();
+        let schedule = schedule.lock().await;
+        let Some(occurrence) = schedule
+            .get_occurrences_on(target.1.date())
+            .into_iter()
+            .find(|occurrence| {
+                occurrence.get_task().get_id() == target.0 && occurrence.get_start() == target.1
+            })
+        else {
+            dbg!("The task to apply the action to is no longer scheduled");
+            return;
+        };
+
+        self.snoozed_map.remove(&target);
+        if self
+            .history
+            .set_status(&occurrence, status, self.clock.now())
+        {
+            self.save_history().await;
+        }
+    }
+
+    pub fn get_viewer_receiver(&self) -> Receiver<()> {
+        self.viewer_receiver.clone()
+    }
+
+    // the previous schedule is kept when the files can't be read or the
+    // schedule is invalid, and the error is returned
+    pub async fn update_contents(&mut self) -> Result<(), String> {
+        let schedule = match self.read_schedule().await {
+            Ok(Ok((schedule, report))) => {
+                self.notify_warnings(&report);
+                schedule
+            }
+            Ok(Err(report)) => {
+                dbg!("Failed to parse schedule file, keeping the previous schedule");
+                dbg!(report.to_string());
+                self.notify_report("⚠ Failed to reload the schedule", &report);
+                return Err(report.to_string());
+            }
+            Err(err) => {
+                dbg!(&err);
+                return Err(err.to_string());
             }
-            Err(_) => (),
+        };
+
+        let task_ids = schedule
+            .get_all_tasks()
+            .map(|task| task.get_id().to_string())
+            .collect::<HashSet<_>>();
+        self.finished_task_map
+            .retain(|((task_id, _), _), _| task_ids.contains(task_id));
+        *self.schedule.lock().await = schedule;
+
+        Ok(())
+    }
+
+    // how long the scheduler can sleep before the next notification is due;
+    // capped so that a suspended laptop or a changed system clock is noticed
+    pub async fn get_time_until_next_notification(&self) -> std::time::Duration {
+        let current_date_time = self.clock.now();
+        let latest_wakeup = current_date_time
+            + chrono::Duration::seconds(self.config.get_max_sleep_seconds().into());
+
+        let next_wakeup = self
+            .schedule
+            .lock()
+            .await
+            .get_next_notification_time(current_date_time, latest_wakeup)
+            .into_iter()
+            .chain(
+                self.snoozed_map
+                    .values()
+                    .copied()
+                    .filter(|until| current_date_time < *until),
+            )
+            .chain(
+                self.get_quiet_ends(current_date_time)
+                    .into_iter()
+                    .filter(|_| !self.digest_queue.is_empty()),
+            )
+            .min()
+            .unwrap_or(latest_wakeup)
+            .min(latest_wakeup);
+
+        self.clock.to_real_duration(next_wakeup - current_date_time)
+    }
+
+    pub fn request_do_not_disturb(&self, minutes: Option<u32>) {
+        if let Err(err) = self
+            .event_sender
+            .try_send(ControllerEvent::DoNotDisturb(minutes))
+        {
+            dbg!(err);
         }
     }
 
+    pub fn set_do_not_disturb(&mut self, minutes: Option<u32>) {
+        self.do_not_disturb_until =
+            minutes.map(|minutes| self.clock.now() + chrono::Duration::minutes(minutes.into()));
+    }
+
+    pub fn get_do_not_disturb_until(&self) -> Option<NaiveDateTime> {
+        let current_date_time = self.clock.now();
+
+        self.do_not_disturb_until
+            .filter(|until| current_date_time < *until)
+    }
+
+    pub fn is_quiet(&self, date_time: NaiveDateTime) -> bool {
+        !self.get_quiet_ends(date_time).is_empty()
+    }
+
+    // when each quiet period in effect at `date_time` ends
+    fn get_quiet_ends(&self, date_time: NaiveDateTime) -> Vec<NaiveDateTime> {
+        self.do_not_disturb_until
+            .filter(|until| date_time < *until)
+            .into_iter()
+            .chain(
+                self.quiet_hours
+                    .and_then(|quiet_hours| quiet_hours.get_end_after(date_time)),
+            )
+            .collect()
+    }
+
     pub async fn check_notifications(&mut self) {
-        let (current_time, current_day_of_week) = {
-            let current_chrono = Local::now();
-            let current_time = current_chrono.time();
+        let current_date_time = self.clock.now();
+        for failure in self.notifier.take_failures() {
+            dbg!(failure);
+        }
+        let due_notifications = self.take_due_notifications().await;
 
-            (current_time, current_chrono.weekday())
-        };
+        // held back while quiet and delivered as one digest afterwards
+        if self.is_quiet(current_date_time) {
+            self.digest_queue.extend(due_notifications);
+            return;
+        }
+        if let Some((summary, body)) = format_digest(&self.digest_queue) {
+            self.digest_queue.clear();
+            self.show_notification(&summary, &body);
+        }
 
-        for day in self.schedule.lock().await.get_days() {
-            if !day.compare_day_of_week(&current_day_of_week) {
-                if cfg!(debug_assertions) {
-                    dbg!(day.get_day_of_week());
-                    dbg!(current_day_of_week.to_string());
+        for notification in due_notifications {
+            if notification.is_silent {
+                if let Err(err) = self
+                    .notifier
+                    .notify_silently(&notification.summary, &notification.body)
+                {
+                    dbg!(err);
+                }
+                if notification.target.is_some() {
+                    self.last_notified_occurrence = notification.target;
                 }
                 continue;
             }
 
-            for task in day.get_tasks() {
-                if cfg!(debug_assertions) {
-                    dbg!(task.get_title());
+            match notification.target {
+                Some(target) => {
+                    let event_sender = self.event_sender.clone();
+                    let on_action = {
+                        let target = target.clone();
+                        Box::new(move |action| {
+                            let event = ControllerEvent::Action(Some(target), action);
+                            if let Err(err) = async_std::task::block_on(event_sender.send(event)) {
+                                dbg!(err);
+                            }
+                        })
+                    };
+                    if let Err(err) = self.notifier.notify_with_actions(
+                        &notification.summary,
+                        &notification.body,
+                        &TASK_ACTIONS,
+                        on_action,
+                    ) {
+                        dbg!(err);
+                    }
+                    self.last_notified_occurrence = Some(target);
                 }
+                None => self.show_notification(&notification.summary, &notification.body),
+            }
+        }
+    }
 
-                let task_memory_address = task.get_memory_address();
+    // the notifications due now, each returned once
+    async fn take_due_notifications(&mut self) -> Vec<DueNotification> {
+        let current_date_time = self.clock.now();
+        let mut due_notifications = Vec::new();
+        let mut missed_notifications = Vec::new();
+
+        let mut is_history_changed = false;
+
+        // on startup, occurrences with a recorded status were already seen by
+        // an earlier run
+        let is_startup = self.last_checked_at.is_none();
+        let catch_up_since = match self.last_checked_at {
+            None => Some(current_date_time.date().and_time(chrono::NaiveTime::MIN)),
+            Some(last_checked_at)
+                if current_date_time - last_checked_at
+                    > chrono::Duration::seconds(
+                        (CATCH_UP_THRESHOLD_FACTOR * self.config.get_max_sleep_seconds()).into(),
+                    ) =>
+            {
+                Some(last_checked_at)
+            }
+            Some(_) => None,
+        };
+        self.last_checked_at = Some(current_date_time);
 
-                if self.finished_task_map.contains_key(&task_memory_address)
-                    && self.finished_task_map[&task_memory_address].load(Ordering::Relaxed)
-                {
-                    continue;
+        let is_retained = |start: &NaiveDateTime| {
+            current_date_time - *start < chrono::Duration::days(OCCURRENCE_RETENTION_DAYS)
+        };
+        self.snoozed_map.retain(|(_, start), _| is_retained(start));
+        self.finished_task_map
+            .retain(|((_, start), _), _| is_retained(start));
+
+        let schedule = self.schedule.clone();
+        let schedule = schedule.lock().await;
+        for occurrence in schedule.get_occurrences_around(current_date_time) {
+            if cfg!(debug_assertions) {
+                dbg!(occurrence.get_task().get_title());
+            }
+
+            let target = (
+                occurrence.get_task().get_id().to_string(),
+                occurrence.get_start(),
+            );
+            let recorded_status = self.history.get_recorded_status(&target);
+            match recorded_status {
+                Some(Status::Done | Status::Skipped) => continue,
+                Some(Status::Missed) => (),
+                _ if occurrence.get_end() <= current_date_time => {
+                    is_history_changed |=
+                        self.history
+                            .set_status(&occurrence, Status::Missed, current_date_time);
                 }
+                _ => (),
+            }
 
-                let (is_converted, task_start_time, task_end_time) = task.get_time_range();
+            if self
+                .snoozed_map
+                .get(&target)
+                .is_some_and(|until| *until <= current_date_time)
+            {
+                let (summary, body) = format_snoozed_notification(occurrence.get_task());
+                self.snoozed_map.remove(&target);
+                due_notifications.push(DueNotification {
+                    summary,
+                    body,
+                    target: Some(target.clone()),
+                    is_silent: occurrence.get_task().is_silent(),
+                });
+            }
 
-                if !is_converted || current_time < task_start_time {
-                    self.finished_task_map
-                        .insert(task_memory_address, AtomicBool::new(false));
-                    continue;
+            for notification in schedule.plan_notifications(&occurrence) {
+                let delivery = self.decide_delivery(&occurrence, &notification, current_date_time);
+                if delivery == Delivery::Missed
+                    && catch_up_since.is_some_and(|since| since <= notification.get_fire_at())
+                    && !(is_startup && recorded_status.is_some())
+                {
+                    missed_notifications.push((notification, occurrence.clone()));
+                }
+                if delivery == Delivery::Show {
+                    if notification.get_kind() == NotificationKind::Start {
+                        is_history_changed |= self.history.set_status(
+                            &occurrence,
+                            Status::InProgress,
+                            current_date_time,
+                        );
+                    }
+                    let next_occurrence = match notification.get_kind() {
+                        NotificationKind::End => schedule.get_next_occurrence(&occurrence),
+                        _ => None,
+                    };
+                    let (summary, body) = format_notification(
+                        occurrence.get_task(),
+                        notification.get_kind(),
+                        next_occurrence.as_ref(),
+                    );
+                    due_notifications.push(DueNotification {
+                        summary,
+                        body,
+                        target: match notification.get_kind() {
+                            NotificationKind::End => None,
+                            _ => Some(target.clone()),
+                        },
+                        is_silent: occurrence.get_task().is_silent(),
+                    });
                 }
+            }
+        }
 
-                self.finished_task_map
-                    .insert(task_memory_address, AtomicBool::new(true));
+        if is_history_changed {
+            self.save_history().await;
+        }
 
-                if current_time > task_end_time {
-                    continue;
-                }
+        missed_notifications.sort_by_key(|(notification, _)| notification.get_fire_at());
+        let mut catch_up_notifications = match self.catch_up_policy {
+            CatchUpPolicy::Summarize => format_missed_summary(
+                &missed_notifications
+                    .iter()
+                    .filter(|(notification, _)| notification.get_kind() == NotificationKind::Start)
+                    .map(|(_, occurrence)| occurrence)
+                    .collect::<Vec<_>>(),
+            )
+            .into_iter()
+            .collect::<Vec<_>>(),
+            CatchUpPolicy::ReplayAll => missed_notifications
+                .iter()
+                .map(|(notification, occurrence)| {
+                    let next_occurrence = match notification.get_kind() {
+                        NotificationKind::End => schedule.get_next_occurrence(occurrence),
+                        _ => None,
+                    };
+                    format_notification(
+                        occurrence.get_task(),
+                        notification.get_kind(),
+                        next_occurrence.as_ref(),
+                    )
+                })
+                .collect(),
+            CatchUpPolicy::Ignore => Vec::new(),
+        }
+        .into_iter()
+        .map(|(summary, body)| DueNotification {
+            summary,
+            body,
+            target: None,
+            is_silent: false,
+        })
+        .collect::<Vec<_>>();
+        catch_up_notifications.extend(due_notifications);
+
+        catch_up_notifications
+    }
+
+    // a notification that was never shown and can no longer be is missed,
+    // which is reported only once
+    fn decide_delivery(
+        &mut self,
+        occurrence: &Occurrence,
+        notification: &PlannedNotification,
+        current_date_time: NaiveDateTime,
+    ) -> Delivery {
+        let key = (
+            (
+                occurrence.get_task().get_id().to_string(),
+                occurrence.get_start(),
+            ),
+            notification.get_kind(),
+        );
+        let occurrence_range = (occurrence.get_start(), occurrence.get_end());
 
-                self.notify_task(task);
+        if let Some(state) = self.finished_task_map.get(&key) {
+            // a task whose time was edited since it was notified is armed again
+            if state.is_finished && state.occurrence_range == occurrence_range {
+                return Delivery::Hold;
             }
         }
+
+        let is_finished = current_date_time >= notification.get_fire_at();
+        self.finished_task_map.insert(
+            key,
+            NotificationState {
+                occurrence_range,
+                is_finished,
+            },
+        );
+
+        match (is_finished, notification.is_due(current_date_time)) {
+            (true, true) => Delivery::Show,
+            (true, false) => Delivery::Missed,
+            (false, _) => Delivery::Hold,
+        }
     }
 
-    fn notify_task(&self, task: &task::Task) {
+    fn show_notification(&self, notification_summary: &str, notification_body: &str) {
         if cfg!(debug_assertions) {
-            dbg!(task.get_title());
+            dbg!(notification_summary);
+        }
+
+        if let Err(err) = self
+            .notifier
+            .notify(notification_summary, notification_body)
+        {
+            dbg!(err);
         }
+    }
+
+    // a schedule that loaded but has warnings
+    fn notify_warnings(&self, report: &ValidationReport) {
+        if report.is_empty() {
+            return;
+        }
+        dbg!("The schedule loaded with warnings");
+        dbg!(report.to_string());
+        self.notify_report("⚠ The schedule loaded with warnings", report);
+    }
+
+    fn notify_report(&self, notification_summary: &str, report: &ValidationReport) {
+        let notification_body = report
+            .get_issues()
+            .iter()
+            .map(|issue| match issue.get_severity() {
+                Severity::Error => format!("✖ {}", issue.get_description()),
+                Severity::Warning => format!("⚠ {}", issue.get_description()),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        self.show_notification(notification_summary, &notification_body);
+    }
+}
+
+fn format_digest(notifications: &[DueNotification]) -> Option<(String, String)> {
+    if notifications.is_empty() {
+        return None;
+    }
+
+    let summary = match notifications.len() {
+        1 => "🔔 1 notification while quiet".to_string(),
+        count => format!("🔔 {} notifications while quiet", count),
+    };
+    let body = notifications
+        .iter()
+        .map(|notification| format!("• {}", notification.summary))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Some((summary, body))
+}
 
-        #[cfg(target_os = "macos")]
-        static SOUND_NAME: &str = "Submarine";
+// missed tasks as a single notification, if any
+fn format_missed_summary(occurrences: &[&Occurrence]) -> Option<(String, String)> {
+    if occurrences.is_empty() {
+        return None;
+    }
 
-        #[cfg(all(unix, not(target_os = "macos")))]
-        static SOUND_NAME: &str = "message-new-instant";
+    let summary = match occurrences.len() {
+        1 => "⏪ Missed 1 task".to_string(),
+        count => format!("⏪ Missed {} tasks", count),
+    };
+    let body = occurrences
+        .iter()
+        .map(|occurrence| {
+            format!(
+                "• {} {}",
+                occurrence.get_start().format("%m/%d %H:%M"),
+                occurrence.get_task().get_title()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
 
-        #[cfg(target_os = "windows")]
-        static SOUND_NAME: &str = "Mail";
+    Some((summary, body))
+}
 
-        let notification_summary = format!("★ {}", task.get_title());
-        let notification_body = format!(
+fn format_snoozed_notification(task: &task::Task) -> (String, String) {
+    (
+        format!("💤 {} (snoozed)", task.get_title()),
+        format!(
             "⌛ {} -- {} \n📖 {}",
-            task.get_start_time(),
-            task.get_end_time(),
+            task::format_time(&task.get_start_time()),
+            task::format_end_time(task),
             task.get_details()
-        );
+        ),
+    )
+}
 
-        Notification::new()
-            .summary(&notification_summary)
-            .body(&notification_body)
-            .sound_name(SOUND_NAME)
-            .show()
-            .unwrap();
+fn format_notification(
+    task: &task::Task,
+    kind: NotificationKind,
+    next_occurrence: Option<&Occurrence>,
+) -> (String, String) {
+    let task_body = format!(
+        "⌛ {} -- {} \n📖 {}",
+        task::format_time(&task.get_start_time()),
+        task::format_end_time(task),
+        task.get_details()
+    );
+
+    match kind {
+        NotificationKind::Reminder { minutes_before } => (
+            format!("⏰ {} (in {} min)", task.get_title(), minutes_before),
+            task_body,
+        ),
+        NotificationKind::Start => (format!("★ {}", task.get_title()), task_body),
+        NotificationKind::EndReminder { minutes_before } => (
+            format!("⏳ {} (ends in {} min)", task.get_title(), minutes_before),
+            task_body,
+        ),
+        NotificationKind::End => (
+            format!("✔ {} ended", task.get_title()),
+            match next_occurrence {
+                Some(next_occurrence) => format!(
+                    "⏭ Next up: {} at {}",
+                    next_occurrence.get_task().get_title(),
+                    task::format_time(&next_occurrence.get_task().get_start_time())
+                ),
+                None => "⏭ Nothing else today".to_string(),
+            },
+        ),
     }
 }
 
@@ -286,34 +918,68 @@ pub trait AsyncLoopInterface {
     async fn wait_for_open_viewer(&self);
 }
 
-const NOTIFICATION_CHECK_INTERVAL: u16 = 100;
+const EVENT_QUEUE_SIZE: usize = 8;
+
+// snoozes and notified states of occurrences that started longer ago are dropped
+const OCCURRENCE_RETENTION_DAYS: i64 = 7;
+
+// a gap between two checks longer than this many times the longest sleep
+// means the machine was asleep or the clock jumped
+const CATCH_UP_THRESHOLD_FACTOR: u32 = 2;
+
+const VIEWER_OPEN_DELAY: u16 = 100;
 
 impl AsyncLoopInterface for Arc<Mutex<Application>> {
+    // sleeps until the next notification is due or an event arrives, without
+    // holding the application lock in between
     async fn async_loop(&self) {
+        let event_receiver = self.lock().await.get_event_receiver();
+
         loop {
             if self.lock().await.check_shutdown() {
                 return;
             }
 
-            self.lock().await.update_contents().await;
-
             self.lock().await.check_notifications().await;
+
+            let sleep_duration = self.lock().await.get_time_until_next_notification().await;
+            match async_std::future::timeout(sleep_duration, event_receiver.recv()).await {
+                // failures are already logged and notified
+                Ok(Ok(ControllerEvent::ScheduleChanged)) => {
+                    let _ = self.lock().await.update_contents().await;
+                }
+                Ok(Ok(ControllerEvent::Action(target, action))) => {
+                    self.lock().await.handle_action(target, action).await;
+                }
+                Ok(Ok(ControllerEvent::DoNotDisturb(minutes))) => {
+                    self.lock().await.set_do_not_disturb(minutes);
+                }
+                Ok(Ok(ControllerEvent::Shutdown)) => (),
+                Ok(Err(_)) => {
+                    dbg!("Failed to receive controller event");
+                    std::process::exit(-1);
+                }
+                Err(_) => (),
+            }
         }
     }
 
     async fn wait_for_open_viewer(&self) {
+        let viewer_receiver = self.lock().await.get_viewer_receiver();
+
         loop {
             if self.lock().await.check_shutdown() {
                 return;
             }
             if !(self.lock().await.check_opened_viewer()) {
+                if viewer_receiver.recv().await.is_err() {
+                    return;
+                }
                 continue;
             }
 
-            async_std::task::sleep(std::time::Duration::from_millis(
-                NOTIFICATION_CHECK_INTERVAL.into(),
-            ))
-            .await;
+            async_std::task::sleep(std::time::Duration::from_millis(VIEWER_OPEN_DELAY.into()))
+                .await;
 
             {
                 static WINDOW_TITLE: &str = "weshce -- schedule viewer";
@@ -327,7 +993,21 @@ impl AsyncLoopInterface for Arc<Mutex<Application>> {
                         .await
                         .clone()
                 };
-                window::open_window(WINDOW_TITLE, schedule_clone);
+                let (history_clone, now, event_sender) = {
+                    let application = self.lock().await;
+                    (
+                        application.get_history().clone(),
+                        application.get_clock().now(),
+                        application.get_event_sender(),
+                    )
+                };
+                let on_action: window::ActionHandler = Arc::new(move |target, action| {
+                    let event = ControllerEvent::Action(Some(target), action);
+                    if let Err(err) = async_std::task::block_on(event_sender.send(event)) {
+                        dbg!(err);
+                    }
+                });
+                window::open_window(WINDOW_TITLE, schedule_clone, history_clone, now, on_action);
             }
 
             self.lock().await.close_viewer();
@@ -339,9 +1019,22 @@ impl AsyncLoopInterface for Arc<Mutex<Application>> {
 mod tests {
     use super::*;
 
+    use super::super::clock::{FixedClock, SystemClock};
+    use super::super::notifier::RecordingNotifier;
+    use chrono::{NaiveDate, Weekday};
+    use std::env;
+
+    fn start_notification(occurrence: &Occurrence) -> PlannedNotification {
+        *occurrence.plan_notifications(&[]).last().unwrap()
+    }
+
     #[async_std::test]
     async fn test_load_schedule() {
-        let mut app = Application::new();
+        let mut app = Application::with_backends(
+            Config::default(),
+            Arc::new(SystemClock),
+            Arc::new(RecordingNotifier::new()),
+        );
 
         app.load_schedule().await.unwrap();
 
@@ -351,17 +1044,641 @@ mod tests {
 
         assert_ne!(schedule.get_days().len(), 0);
 
-        assert_eq!(schedule.get_days()[0].get_day_of_week(), "Monday");
-        assert_eq!(schedule.get_days()[1].get_day_of_week(), "Tuesday");
-        assert_eq!(schedule.get_days()[2].get_day_of_week(), "Wednesday");
-        assert_eq!(schedule.get_days()[3].get_day_of_week(), "Thursday");
-        assert_eq!(schedule.get_days()[4].get_day_of_week(), "Friday");
-        assert_eq!(schedule.get_days()[5].get_day_of_week(), "Saturday");
-        assert_eq!(schedule.get_days()[6].get_day_of_week(), "Sunday");
+        assert_eq!(
+            schedule.get_days()[0].get_day_of_week().get_weekday(),
+            Weekday::Mon
+        );
+        assert_eq!(
+            schedule.get_days()[1].get_day_of_week().get_weekday(),
+            Weekday::Tue
+        );
+        assert_eq!(
+            schedule.get_days()[2].get_day_of_week().get_weekday(),
+            Weekday::Wed
+        );
+        assert_eq!(
+            schedule.get_days()[3].get_day_of_week().get_weekday(),
+            Weekday::Thu
+        );
+        assert_eq!(
+            schedule.get_days()[4].get_day_of_week().get_weekday(),
+            Weekday::Fri
+        );
+        assert_eq!(
+            schedule.get_days()[5].get_day_of_week().get_weekday(),
+            Weekday::Sat
+        );
+        assert_eq!(
+            schedule.get_days()[6].get_day_of_week().get_weekday(),
+            Weekday::Sun
+        );
 
         assert_eq!(
             schedule.get_days()[0].get_tasks()[0].get_title(),
             "Team Meeting"
         );
     }
+
+    #[test]
+    fn test_notified_state_survives_reload() {
+        let load = |json: &str| serde_json::from_str::<Schedule>(json).unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 11, 2).unwrap();
+        let now = date.and_hms_opt(9, 30, 0).unwrap();
+        let decide = |app: &mut Application, schedule: &Schedule, now| {
+            let occurrence = &schedule.get_occurrences_on(date)[0];
+            app.decide_delivery(occurrence, &start_notification(occurrence), now)
+        };
+
+        let mut app = Application::new(Config::default());
+        let original = load(
+            r#"{ "days": [{ "day_of_week": "Monday", "tasks": [
+                { "id": "standup", "title": "Standup", "start_time": "09:00", "end_time": "10:00", "details": "" }
+            ] }] }"#,
+        );
+
+        assert_eq!(decide(&mut app, &original, now), Delivery::Show);
+        assert_eq!(decide(&mut app, &original, now), Delivery::Hold);
+
+        let reloaded = load(&serde_json::to_string(&original).unwrap());
+        assert_eq!(decide(&mut app, &reloaded, now), Delivery::Hold);
+
+        let rescheduled = load(
+            r#"{ "days": [{ "day_of_week": "Monday", "tasks": [
+                { "id": "standup", "title": "Standup", "start_time": "09:15", "end_time": "10:00", "details": "" }
+            ] }] }"#,
+        );
+        assert_eq!(decide(&mut app, &rescheduled, now), Delivery::Show);
+
+        let next_week = date + chrono::Duration::days(7);
+        let occurrence = &rescheduled.get_occurrences_on(next_week)[0];
+        assert_eq!(
+            app.decide_delivery(
+                occurrence,
+                &start_notification(occurrence),
+                next_week.and_hms_opt(9, 30, 0).unwrap()
+            ),
+            Delivery::Show
+        );
+
+        let week_after = next_week + chrono::Duration::days(7);
+        let occurrence = &rescheduled.get_occurrences_on(week_after)[0];
+        assert_eq!(
+            app.decide_delivery(
+                occurrence,
+                &start_notification(occurrence),
+                week_after.and_hms_opt(9, 0, 0).unwrap()
+            ),
+            Delivery::Hold
+        );
+        assert_eq!(
+            app.decide_delivery(
+                occurrence,
+                &start_notification(occurrence),
+                week_after.and_hms_opt(10, 30, 0).unwrap()
+            ),
+            Delivery::Missed
+        );
+    }
+
+    #[async_std::test]
+    async fn test_daily_task_next_to_overnight_task_notifies_once() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{ "days": [{ "day_of_week": "Monday", "tasks": [
+                { "id": "work", "title": "Work", "start_time": "09:00", "end_time": "17:00", "details": "",
+                  "recurrence": { "rule": "FREQ=DAILY" } },
+                { "title": "Night shift", "start_time": "23:00", "end_time": "07:00", "details": "" }
+            ] }] }"#,
+        )
+        .unwrap();
+        let tuesday = NaiveDate::from_ymd_opt(2026, 10, 20).unwrap();
+        let clock = Arc::new(FixedClock::new(tuesday.and_hms_opt(9, 0, 0).unwrap()));
+        let recorder = Arc::new(RecordingNotifier::new());
+
+        let mut app =
+            Application::with_backends(Config::default(), clock.clone(), recorder.clone());
+        *app.get_schedule().lock().await = schedule;
+
+        app.check_notifications().await;
+        assert_eq!(
+            recorder
+                .get_notifications()
+                .iter()
+                .map(|(summary, _)| summary.as_str())
+                .collect::<Vec<_>>(),
+            ["★ Work"]
+        );
+
+        clock.advance(chrono::Duration::minutes(1));
+        app.check_notifications().await;
+        assert_eq!(recorder.get_notifications().len(), 1);
+    }
+
+    #[async_std::test]
+    async fn test_reload_with_warnings_notifies() {
+        let directory = env::temp_dir().join(format!("wesche-warnings-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("schedule.json"), r#"{ "days": [] }"#).unwrap();
+        let record = toml::from_str(
+            r#"
+            schedules = ["schedule.json"]
+            history = "history.json"
+            "#,
+        )
+        .unwrap();
+        let config = Config::from_record(record, &directory).unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2026, 10, 26).unwrap();
+        let clock = Arc::new(FixedClock::new(date.and_hms_opt(10, 0, 0).unwrap()));
+        let recorder = Arc::new(RecordingNotifier::new());
+        let mut app = Application::with_backends(config, clock, recorder.clone());
+        app.load_schedule().await.unwrap();
+        assert!(recorder.get_notifications().is_empty());
+
+        std::fs::write(
+            directory.join("schedule.json"),
+            r#"{ "days": [
+                { "day_of_week": "Monday", "tasks": [] },
+                { "day_of_week": "Monday", "tasks": [] }
+            ] }"#,
+        )
+        .unwrap();
+        app.update_contents().await.unwrap();
+
+        let notifications = recorder.get_notifications();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].0, "⚠ The schedule loaded with warnings");
+        assert!(notifications[0].1.contains("duplicate day"));
+
+        std::fs::write(directory.join("schedule.json"), "{ \"days\": ").unwrap();
+        assert!(app.update_contents().await.is_err());
+        assert_eq!(app.get_schedule().lock().await.get_days().len(), 2);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[async_std::test]
+    async fn test_reload_keeps_notified_state_of_added_tasks() {
+        let directory = env::temp_dir().join(format!("wesche-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(
+            directory.join("schedule.json"),
+            r#"{ "days": [], "overrides": { "2026-10-26": { "add": [
+                { "id": "offsite", "title": "Offsite", "start_time": "10:00", "end_time": "17:00", "details": "" }
+            ] } } }"#,
+        )
+        .unwrap();
+        let record = toml::from_str(
+            r#"
+            schedules = ["schedule.json"]
+            history = "history.json"
+            "#,
+        )
+        .unwrap();
+        let config = Config::from_record(record, &directory).unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2026, 10, 26).unwrap();
+        let clock = Arc::new(FixedClock::new(date.and_hms_opt(10, 0, 0).unwrap()));
+        let recorder = Arc::new(RecordingNotifier::new());
+        let mut app = Application::with_backends(config, clock.clone(), recorder.clone());
+        app.load_schedule().await.unwrap();
+
+        app.check_notifications().await;
+        assert_eq!(recorder.get_notifications().len(), 1);
+
+        app.update_contents().await.unwrap();
+        clock.advance(chrono::Duration::minutes(1));
+        app.check_notifications().await;
+        assert_eq!(recorder.get_notifications().len(), 1);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_overnight_task_notifies_after_midnight() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{ "days": [{ "day_of_week": "Sunday", "tasks": [
+                { "title": "Night shift", "start_time": "23:30", "end_time": "01:00", "details": "" }
+            ] }] }"#,
+        )
+        .unwrap();
+        let after_midnight = NaiveDate::from_ymd_opt(2026, 10, 19)
+            .unwrap()
+            .and_hms_opt(0, 15, 0)
+            .unwrap();
+
+        let mut app = Application::new(Config::default());
+        let occurrences = schedule.get_occurrences_around(after_midnight);
+        assert_eq!(occurrences.len(), 1);
+        let notification = start_notification(&occurrences[0]);
+        assert_eq!(
+            app.decide_delivery(&occurrences[0], &notification, after_midnight),
+            Delivery::Show
+        );
+        assert_eq!(
+            app.decide_delivery(&occurrences[0], &notification, after_midnight),
+            Delivery::Hold
+        );
+    }
+
+    #[test]
+    fn test_each_reminder_notifies_once() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{ "reminders": [10], "days": [{ "day_of_week": "Monday", "tasks": [
+                { "title": "Standup", "start_time": "09:00", "end_time": "09:15", "reminders": [15, 5], "details": "" }
+            ] }] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+
+        let mut app = Application::new(Config::default());
+        let mut notified = Vec::new();
+        for minute in (8 * 60 + 40)..(9 * 60 + 5) {
+            let now = monday.and_hms_opt(minute / 60, minute % 60, 0).unwrap();
+            for occurrence in schedule.get_occurrences_around(now) {
+                for notification in schedule.plan_notifications(&occurrence) {
+                    if app.decide_delivery(&occurrence, &notification, now) == Delivery::Show {
+                        notified.push((now.time(), notification.get_kind()));
+                    }
+                }
+            }
+        }
+
+        let at = |hour, minute| chrono::NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        assert_eq!(
+            notified,
+            vec![
+                (at(8, 45), NotificationKind::Reminder { minutes_before: 15 }),
+                (at(8, 55), NotificationKind::Reminder { minutes_before: 5 }),
+                (at(9, 0), NotificationKind::Start),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reminder_is_planned_the_day_before() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{ "reminders": [30], "days": [{ "day_of_week": "Tuesday", "tasks": [
+                { "title": "Early run", "start_time": "00:10", "end_time": "01:00", "details": "" }
+            ] }] }"#,
+        )
+        .unwrap();
+        let monday_night = NaiveDate::from_ymd_opt(2026, 10, 19)
+            .unwrap()
+            .and_hms_opt(23, 45, 0)
+            .unwrap();
+
+        let occurrences = schedule.get_occurrences_around(monday_night);
+        assert_eq!(occurrences.len(), 1);
+        let reminder = occurrences[0].plan_notifications(&[30])[0];
+        assert_eq!(
+            Application::new(Config::default()).decide_delivery(
+                &occurrences[0],
+                &reminder,
+                monday_night
+            ),
+            Delivery::Show
+        );
+    }
+
+    #[test]
+    fn test_end_notification_hands_off_to_next_task() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{ "notify_end": true, "days": [{ "day_of_week": "Monday", "tasks": [
+                { "title": "Exercise", "start_time": "19:00", "end_time": "19:35", "end_reminders": [5], "details": "" },
+                { "title": "Game break", "start_time": "19:35", "end_time": "20:35", "details": "" },
+                { "title": "Study", "start_time": "20:35", "end_time": "21:30", "notify_end": false, "details": "" }
+            ] }] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+
+        let mut app = Application::new(Config::default());
+        let mut summaries = Vec::new();
+        for minute in (19 * 60)..(22 * 60) {
+            let now = monday.and_hms_opt(minute / 60, minute % 60, 0).unwrap();
+            for occurrence in schedule.get_occurrences_around(now) {
+                for notification in schedule.plan_notifications(&occurrence) {
+                    if app.decide_delivery(&occurrence, &notification, now) == Delivery::Show {
+                        let next_occurrence = schedule.get_next_occurrence(&occurrence);
+                        let (summary, body) = format_notification(
+                            occurrence.get_task(),
+                            notification.get_kind(),
+                            next_occurrence.as_ref(),
+                        );
+                        summaries.push(match notification.get_kind() {
+                            NotificationKind::End => format!("{} / {}", summary, body),
+                            _ => summary,
+                        });
+                    }
+                }
+            }
+        }
+
+        assert_eq!(
+            summaries,
+            vec![
+                "★ Exercise",
+                "⏳ Exercise (ends in 5 min)",
+                "✔ Exercise ended / ⏭ Next up: Game break at 19:35",
+                "★ Game break",
+                "✔ Game break ended / ⏭ Next up: Study at 20:35",
+                "★ Study",
+            ]
+        );
+    }
+
+    #[async_std::test]
+    async fn test_simulated_week() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{ "reminders": [1], "days": [
+                { "day_of_week": "Monday", "tasks": [
+                    { "title": "Standup", "start_time": "09:00", "end_time": "09:15", "details": "" }
+                ] },
+                { "day_of_week": "Thursday", "tasks": [
+                    { "title": "Dinner prep", "start_time": "17:30", "end_time": "18:00", "details": "" }
+                ] }
+            ] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let clock = Arc::new(FixedClock::new(monday.and_hms_opt(0, 0, 0).unwrap()));
+
+        let recorder = Arc::new(RecordingNotifier::new());
+
+        let mut app =
+            Application::with_backends(Config::default(), clock.clone(), recorder.clone());
+        *app.get_schedule().lock().await = schedule;
+
+        let mut notified = Vec::new();
+        for _ in 0..(7 * 24 * 60) {
+            app.check_notifications().await;
+            notified.extend(
+                recorder
+                    .get_notifications()
+                    .into_iter()
+                    .skip(notified.len())
+                    .map(|(summary, _)| (clock.now().format("%a %H:%M").to_string(), summary)),
+            );
+            clock.advance(chrono::Duration::minutes(1));
+        }
+
+        let expected = [
+            ("Mon 08:59", "⏰ Standup (in 1 min)"),
+            ("Mon 09:00", "★ Standup"),
+            ("Thu 17:29", "⏰ Dinner prep (in 1 min)"),
+            ("Thu 17:30", "★ Dinner prep"),
+        ];
+        assert_eq!(
+            notified,
+            expected
+                .iter()
+                .map(|(at, summary)| (at.to_string(), summary.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[async_std::test]
+    async fn test_snooze_and_done_actions() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{ "notify_end": true, "days": [{ "day_of_week": "Monday", "tasks": [
+                { "title": "Standup", "start_time": "09:00", "end_time": "09:30", "details": "" },
+                { "title": "Review", "start_time": "10:00", "end_time": "10:30", "details": "" }
+            ] }] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let clock = Arc::new(FixedClock::new(monday.and_hms_opt(9, 0, 0).unwrap()));
+        let recorder = Arc::new(RecordingNotifier::new());
+
+        let mut app =
+            Application::with_backends(Config::default(), clock.clone(), recorder.clone());
+        *app.get_schedule().lock().await = schedule;
+
+        let mut notified = Vec::new();
+        for minute in 0..(2 * 60) {
+            app.check_notifications().await;
+            notified.extend(
+                recorder
+                    .get_notifications()
+                    .into_iter()
+                    .skip(notified.len())
+                    .map(|(summary, _)| (clock.now().format("%H:%M").to_string(), summary)),
+            );
+
+            // acting from the tray applies to the last task notification
+            match minute {
+                0 => {
+                    app.handle_action(None, NotificationAction::Snooze { minutes: 5 })
+                        .await
+                }
+                5 => app.handle_action(None, NotificationAction::Done).await,
+                60 => app.handle_action(None, NotificationAction::Skip).await,
+                _ => (),
+            }
+            clock.advance(chrono::Duration::minutes(1));
+        }
+
+        let expected = [
+            ("09:00", "★ Standup"),
+            ("09:05", "💤 Standup (snoozed)"),
+            ("10:00", "★ Review"),
+        ];
+        assert_eq!(
+            notified,
+            expected
+                .iter()
+                .map(|(at, summary)| (at.to_string(), summary.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[async_std::test]
+    async fn test_history_is_recorded_and_persisted() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{ "days": [{ "day_of_week": "Monday", "tasks": [
+                { "id": "standup", "title": "Standup", "start_time": "09:00", "end_time": "09:15", "details": "" },
+                { "id": "review", "title": "Review", "start_time": "10:00", "end_time": "10:30", "details": "" }
+            ] }] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let at = |hour, minute| monday.and_hms_opt(hour, minute, 0).unwrap();
+        let history_file_path =
+            env::temp_dir().join(format!("wesche-history-{}.json", std::process::id()));
+        let history_file_path = history_file_path.to_str().unwrap();
+        let _ = std::fs::remove_file(history_file_path);
+
+        let clock = Arc::new(FixedClock::new(at(9, 0)));
+        let mut app = Application::with_backends(
+            Config::default(),
+            clock.clone(),
+            Arc::new(RecordingNotifier::new()),
+        );
+        *app.get_schedule().lock().await = schedule.clone();
+        app.load_history(history_file_path).await;
+
+        app.check_notifications().await;
+        app.handle_action(None, NotificationAction::Done).await;
+        clock.advance(chrono::Duration::hours(2));
+        app.check_notifications().await;
+
+        let mut reloaded = Application::with_backends(
+            Config::default(),
+            clock.clone(),
+            Arc::new(RecordingNotifier::new()),
+        );
+        reloaded.load_history(history_file_path).await;
+        let _ = std::fs::remove_file(history_file_path);
+
+        let statuses = schedule
+            .get_occurrences_on(monday)
+            .iter()
+            .map(|occurrence| reloaded.get_history().get_status(occurrence, at(12, 0)))
+            .collect::<Vec<_>>();
+        assert_eq!(statuses, vec![Status::Done, Status::Missed]);
+    }
+
+    #[async_std::test]
+    async fn test_catch_up_after_sleep() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{ "days": [{ "day_of_week": "Monday", "tasks": [
+                { "title": "Standup", "start_time": "09:00", "end_time": "09:30", "details": "" },
+                { "title": "Deep work", "start_time": "10:00", "end_time": "11:00", "details": "" },
+                { "title": "Lunch", "start_time": "12:00", "end_time": "13:00", "details": "" }
+            ] }] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let at = |hour, minute| monday.and_hms_opt(hour, minute, 0).unwrap();
+
+        let summaries_after_sleep = |policy| {
+            let schedule = schedule.clone();
+            async move {
+                let clock = Arc::new(FixedClock::new(at(8, 0)));
+                let recorder = Arc::new(RecordingNotifier::new());
+                let mut app =
+                    Application::with_backends(Config::default(), clock.clone(), recorder.clone());
+                app.catch_up_policy = policy;
+                *app.get_schedule().lock().await = schedule;
+
+                app.check_notifications().await;
+                clock.set(at(10, 30));
+                app.check_notifications().await;
+
+                recorder
+                    .get_notifications()
+                    .into_iter()
+                    .map(|(summary, body)| {
+                        format!("{} / {}", summary, body.lines().next().unwrap())
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            summaries_after_sleep(CatchUpPolicy::Summarize).await,
+            vec![
+                "⏪ Missed 1 task / • 10/19 09:00 Standup",
+                "★ Deep work / ⌛ 10:00 -- 11:00 ",
+            ]
+        );
+        assert_eq!(
+            summaries_after_sleep(CatchUpPolicy::ReplayAll).await,
+            vec![
+                "★ Standup / ⌛ 09:00 -- 09:30 ",
+                "★ Deep work / ⌛ 10:00 -- 11:00 ",
+            ]
+        );
+        assert_eq!(
+            summaries_after_sleep(CatchUpPolicy::Ignore).await,
+            vec!["★ Deep work / ⌛ 10:00 -- 11:00 "]
+        );
+    }
+
+    #[async_std::test]
+    async fn test_catch_up_on_late_start() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{ "days": [{ "day_of_week": "Monday", "tasks": [
+                { "title": "Standup", "start_time": "09:00", "end_time": "09:30", "details": "" },
+                { "title": "Review", "start_time": "09:45", "end_time": "10:00", "details": "" },
+                { "title": "Deep work", "start_time": "10:00", "end_time": "11:00", "details": "" }
+            ] }] }"#,
+        )
+        .unwrap();
+        let clock = Arc::new(FixedClock::new(
+            NaiveDate::from_ymd_opt(2026, 10, 19)
+                .unwrap()
+                .and_hms_opt(10, 30, 0)
+                .unwrap(),
+        ));
+        let recorder = Arc::new(RecordingNotifier::new());
+
+        let mut app = Application::with_backends(Config::default(), clock, recorder.clone());
+        app.catch_up_policy = CatchUpPolicy::Summarize;
+        *app.get_schedule().lock().await = schedule;
+        app.check_notifications().await;
+
+        let notifications = recorder.get_notifications();
+        assert_eq!(notifications.len(), 2);
+        assert_eq!(
+            notifications[0],
+            (
+                "⏪ Missed 2 tasks".to_string(),
+                "• 10/19 09:00 Standup\n• 10/19 09:45 Review".to_string()
+            )
+        );
+        assert_eq!(notifications[1].0, "★ Deep work");
+    }
+
+    #[async_std::test]
+    async fn test_quiet_hours_and_do_not_disturb_digest() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{ "days": [{ "day_of_week": "Monday", "tasks": [
+                { "title": "Lunch", "start_time": "12:15", "end_time": "12:45", "details": "" },
+                { "title": "Water", "start_time": "13:02", "end_time": "13:03", "silent": true, "details": "" },
+                { "title": "Review", "start_time": "14:10", "end_time": "14:20", "details": "" }
+            ] }] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let clock = Arc::new(FixedClock::new(monday.and_hms_opt(11, 55, 0).unwrap()));
+        let recorder = Arc::new(RecordingNotifier::new());
+        let mut app =
+            Application::with_backends(Config::default(), clock.clone(), recorder.clone());
+        app.quiet_hours = Some("12:00-13:00".parse().unwrap());
+        *app.get_schedule().lock().await = schedule;
+
+        let summaries = |recorder: &RecordingNotifier| {
+            recorder
+                .get_notifications()
+                .into_iter()
+                .map(|(summary, body)| format!("{} / {}", summary, body.lines().next().unwrap()))
+                .collect::<Vec<_>>()
+        };
+
+        while clock.now() < monday.and_hms_opt(13, 5, 0).unwrap() {
+            app.check_notifications().await;
+            clock.advance(chrono::Duration::minutes(1));
+        }
+        assert_eq!(
+            summaries(&recorder),
+            vec![
+                "🔔 1 notification while quiet / • ★ Lunch",
+                "★ Water / ⌛ 13:02 -- 13:03 ",
+            ]
+        );
+        assert_eq!(recorder.get_silent_summaries(), vec!["★ Water"]);
+
+        clock.set(monday.and_hms_opt(14, 0, 0).unwrap());
+        app.check_notifications().await;
+        app.set_do_not_disturb(Some(30));
+        while clock.now() < monday.and_hms_opt(14, 31, 0).unwrap() {
+            app.check_notifications().await;
+            clock.advance(chrono::Duration::minutes(1));
+        }
+        assert_eq!(
+            summaries(&recorder)[2..],
+            ["🔔 1 notification while quiet / • ★ Review"]
+        );
+    }
 }
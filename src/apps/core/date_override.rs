@@ -0,0 +1,146 @@
+use super::task::{self, Task};
+use super::validation::{Issue, Severity};
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+// An exception to the weekly template for a single calendar date.
+// `cancel` refers to weekly tasks by id or title, and an added task whose id
+// matches a weekly task replaces it.
+#[derive(Deserialize, Clone, Debug)]
+pub struct DateOverrideRecord {
+    #[serde(default)]
+    replace_all: bool,
+    #[serde(default)]
+    cancel: Vec<String>,
+    #[serde(default)]
+    add: Vec<task::TaskRecord>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DateOverride {
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    replace_all: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    cancel: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    add: Vec<Task>,
+}
+
+impl DateOverride {
+    pub fn from_record(
+        record: DateOverrideRecord,
+        date: NaiveDate,
+    ) -> Result<DateOverride, Vec<Issue>> {
+        let mut add = Vec::new();
+        let mut issues = Vec::new();
+
+        for (idx, task) in record.add.into_iter().enumerate() {
+            match Task::from_record(task, date.weekday()) {
+                Ok(task) => add.push(task),
+                Err(errors) => {
+                    let location = format!("{}: add #{}", date, idx);
+                    issues.extend(
+                        errors
+                            .iter()
+                            .map(|err| Issue::new(Severity::Error, &location, err)),
+                    );
+                }
+            }
+        }
+
+        if !issues.is_empty() {
+            return Err(issues);
+        }
+
+        Ok(DateOverride {
+            replace_all: record.replace_all,
+            cancel: record.cancel,
+            add,
+        })
+    }
+
+    pub fn get_added_tasks(&self) -> &Vec<Task> {
+        self.add.as_ref()
+    }
+
+    pub fn is_removed(&self, task: &Task) -> bool {
+        self.replace_all
+            || self
+                .cancel
+                .iter()
+                .any(|reference| reference == task.get_id() || reference == task.get_title())
+            || self.add.iter().any(|added| added.get_id() == task.get_id())
+    }
+
+    pub fn validate(&self, date: NaiveDate, weekly_tasks: &[&Task]) -> Vec<Issue> {
+        let zero_end_reminders = self
+            .add
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| task.get_end_reminders().is_some_and(|r| r.contains(&0)))
+            .map(|(idx, _)| {
+                Issue::new(
+                    Severity::Warning,
+                    &format!("{}: add #{}", date, idx),
+                    task::ZERO_END_REMINDER,
+                )
+            });
+
+        self.cancel
+            .iter()
+            .filter(|reference| {
+                !weekly_tasks.iter().any(|task| {
+                    task.get_id() == reference.as_str() || task.get_title() == reference.as_str()
+                })
+            })
+            .map(|reference| {
+                Issue::new(
+                    Severity::Warning,
+                    &date.to_string(),
+                    &format!("cancel \"{}\" matches no task on this date", reference),
+                )
+            })
+            .chain(zero_end_reminders)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_override() {
+        let date = NaiveDate::from_ymd_opt(2026, 11, 3).unwrap();
+        let record = serde_json::from_str::<DateOverrideRecord>(
+            r#"{
+                "cancel": ["Gym"],
+                "add": [{ "id": "standup", "title": "Late standup", "start_time": "10:00", "end_time": "10:15", "details": "" }]
+            }"#,
+        )
+        .unwrap();
+        let date_override = DateOverride::from_record(record, date).unwrap();
+
+        let weekly = serde_json::from_str::<Vec<task::TaskRecord>>(
+            r#"[
+                { "title": "Gym", "start_time": "19:00", "end_time": "20:00", "details": "" },
+                { "id": "standup", "title": "Standup", "start_time": "09:00", "end_time": "09:15", "details": "" },
+                { "title": "Lunch", "start_time": "12:00", "end_time": "13:00", "details": "" }
+            ]"#,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|record| Task::from_record(record, date.weekday()).unwrap())
+        .collect::<Vec<_>>();
+
+        let removed = weekly
+            .iter()
+            .map(|task| date_override.is_removed(task))
+            .collect::<Vec<_>>();
+        assert_eq!(removed, vec![true, true, false]);
+        assert!(date_override
+            .validate(date, &weekly.iter().collect::<Vec<_>>())
+            .is_empty());
+    }
+}
@@ -1,32 +1,139 @@
+use super::day_of_week::{DayOfWeek, Language};
 use super::task;
+use super::validation::{Issue, Severity};
 
-use serde::Deserialize;
+use chrono::Timelike;
+use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Clone, Debug)]
-pub struct Day {
+pub struct DayRecord {
     day_of_week: String,
+    tasks: Vec<task::TaskRecord>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(try_from = "DayRecord")]
+pub struct Day {
+    day_of_week: DayOfWeek,
     tasks: Vec<task::Task>,
 }
 
+impl TryFrom<DayRecord> for Day {
+    type Error = String;
+
+    fn try_from(record: DayRecord) -> Result<Self, Self::Error> {
+        Day::from_record(record).map_err(|issues| {
+            issues
+                .iter()
+                .map(|issue| issue.get_description())
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+    }
+}
+
 impl Day {
-    pub fn get_day_of_week(&self) -> &str {
-        self.day_of_week.as_str()
+    pub fn from_record(record: DayRecord) -> Result<Day, Vec<Issue>> {
+        let day_of_week = record
+            .day_of_week
+            .parse::<DayOfWeek>()
+            .map_err(|err| vec![Issue::new(Severity::Error, "", &err)])?;
+
+        let mut tasks = Vec::new();
+        let mut issues = Vec::new();
+        for (idx, task) in record.tasks.into_iter().enumerate() {
+            match task::Task::from_record(task, day_of_week.get_weekday()) {
+                Ok(task) => tasks.push(task),
+                Err(errors) => {
+                    let location = format!("{}: task #{}", record.day_of_week, idx);
+                    issues.extend(
+                        errors
+                            .iter()
+                            .map(|err| Issue::new(Severity::Error, &location, err)),
+                    );
+                }
+            }
+        }
+
+        if !issues.is_empty() {
+            return Err(issues);
+        }
+
+        Ok(Day { day_of_week, tasks })
+    }
+
+    pub fn get_day_of_week(&self) -> &DayOfWeek {
+        &self.day_of_week
     }
 
     pub fn get_tasks(&self) -> &Vec<task::Task> {
         self.tasks.as_ref()
     }
 
-    pub fn compare_day_of_week(&self, week_day: &chrono::Weekday) -> bool {
-        match week_day {
-            chrono::Weekday::Mon => self.day_of_week == "Monday" || self.day_of_week == "月曜日",
-            chrono::Weekday::Tue => self.day_of_week == "Tuesday" || self.day_of_week == "火曜日",
-            chrono::Weekday::Wed => self.day_of_week == "Wednesday" || self.day_of_week == "水曜日",
-            chrono::Weekday::Thu => self.day_of_week == "Thursday" || self.day_of_week == "木曜日",
-            chrono::Weekday::Fri => self.day_of_week == "Friday" || self.day_of_week == "金曜日",
-            chrono::Weekday::Sat => self.day_of_week == "Saturday" || self.day_of_week == "土曜日",
-            chrono::Weekday::Sun => self.day_of_week == "Sunday" || self.day_of_week == "日曜日",
+    pub fn set_display_language(&mut self, language: Language) {
+        self.day_of_week.set_language(language);
+    }
+
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        let location_of = |idx: usize| format!("{}: task #{}", self.day_of_week, idx);
+        // seconds since the start of this day, so overnight tasks extend past 24h
+        let span_of = |task: &task::Task| {
+            let start = i64::from(task.get_start_time().num_seconds_from_midnight());
+            (start, start + task.get_duration().num_seconds())
+        };
+
+        for (idx, task) in self.tasks.iter().enumerate() {
+            if task.get_title().trim().is_empty() {
+                issues.push(Issue::new(
+                    Severity::Warning,
+                    &location_of(idx),
+                    "empty title",
+                ));
+            }
+
+            if task.get_end_reminders().is_some_and(|r| r.contains(&0)) {
+                issues.push(Issue::new(
+                    Severity::Warning,
+                    &location_of(idx),
+                    task::ZERO_END_REMINDER,
+                ));
+            }
+
+            if task.get_explicit_end_day_offset() == Some(0)
+                && task.get_end_time() < task.get_start_time()
+            {
+                issues.push(Issue::new(
+                    Severity::Error,
+                    &location_of(idx),
+                    &format!(
+                        "end_time {} is before start_time {} on the same day",
+                        task::format_time(&task.get_end_time()),
+                        task::format_time(&task.get_start_time())
+                    ),
+                ));
+                continue;
+            }
+
+            let (start, end) = span_of(task);
+            for (other_idx, other) in self.tasks.iter().enumerate().skip(idx + 1) {
+                let (other_start, other_end) = span_of(other);
+
+                if other_end >= other_start && start < other_end && other_start < end {
+                    issues.push(Issue::new(
+                        Severity::Warning,
+                        &location_of(idx),
+                        &format!(
+                            "overlaps with task #{} \"{}\"",
+                            other_idx,
+                            other.get_title()
+                        ),
+                    ));
+                }
+            }
         }
+
+        issues
     }
 }
 
@@ -34,13 +141,76 @@ impl Day {
 mod tests {
     use super::*;
 
+    use chrono::Weekday;
+
     #[test]
     fn test_day() {
-        let day = Day {
-            day_of_week: "Monday".to_string(),
-            tasks: vec![],
-        };
+        let day = serde_json::from_str::<Day>(r#"{ "day_of_week": "月曜", "tasks": [] }"#).unwrap();
+
+        assert_eq!(day.get_day_of_week().get_weekday(), Weekday::Mon);
+        assert_eq!(day.get_day_of_week().to_string(), "月曜日");
+    }
+
+    #[test]
+    fn test_unknown_day_of_week() {
+        let result = serde_json::from_str::<Day>(r#"{ "day_of_week": "Funday", "tasks": [] }"#);
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("unknown day of week \"Funday\""));
+    }
+
+    #[test]
+    fn test_invalid_task_time() {
+        let result = serde_json::from_str::<Day>(
+            r#"{
+                "day_of_week": "Tuesday",
+                "tasks": [
+                    { "title": "ok", "start_time": "09:00", "end_time": "10:00", "details": "" },
+                    { "title": "bad", "start_time": "10:00", "end_time": "1O:30", "details": "" }
+                ]
+            }"#,
+        );
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("Tuesday: task #1: invalid end_time \"1O:30\""));
+    }
+
+    #[test]
+    fn test_validate_day() {
+        let day = serde_json::from_str::<Day>(
+            r#"{
+                "day_of_week": "Friday",
+                "tasks": [
+                    { "title": "", "start_time": "09:00", "end_time": "10:00", "details": "" },
+                    { "title": "b", "start_time": "09:30", "end_time": "11:00", "details": "" },
+                    { "title": "c", "start_time": "11:00", "end_time": "10:00", "end_day_offset": 0, "details": "" },
+                    { "title": "d", "start_time": "11:00", "end_time": "12:00", "end_reminders": [0], "details": "" },
+                    { "title": "e", "start_time": "23:00", "end_time": "01:00", "details": "" },
+                    { "title": "f", "start_time": "23:30", "end_time": "23:45", "details": "" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let issues = day
+            .validate()
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect::<Vec<_>>();
 
-        assert_eq!(day.get_day_of_week(), "Moday");
+        assert_eq!(
+            issues,
+            vec![
+                "warning: Friday: task #0: empty title",
+                "warning: Friday: task #0: overlaps with task #1 \"b\"",
+                "error: Friday: task #2: end_time 10:00 is before start_time 11:00 on the same day",
+                "warning: Friday: task #3: 0-minute end reminder never fires; use notify_end",
+                "warning: Friday: task #4: overlaps with task #5 \"f\"",
+            ]
+        );
     }
 }
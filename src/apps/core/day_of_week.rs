@@ -0,0 +1,158 @@
+use chrono::Weekday;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+const ENGLISH_NAMES: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+const JAPANESE_NAMES: [&str; 7] = ["月", "火", "水", "木", "金", "土", "日"];
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    English,
+    Japanese,
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_lowercase().as_str() {
+            "en" | "english" => Ok(Language::English),
+            "ja" | "japanese" | "日本語" => Ok(Language::Japanese),
+            _ => Err(format!("unknown language \"{}\"", name)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DayOfWeek {
+    weekday: Weekday,
+    language: Language,
+}
+
+impl DayOfWeek {
+    pub fn new(weekday: Weekday, language: Language) -> DayOfWeek {
+        DayOfWeek { weekday, language }
+    }
+
+    pub fn get_weekday(&self) -> Weekday {
+        self.weekday
+    }
+
+    pub fn get_language(&self) -> Language {
+        self.language
+    }
+
+    pub fn set_language(&mut self, language: Language) {
+        self.language = language;
+    }
+
+    pub fn get_name(&self) -> String {
+        let idx = self.weekday.num_days_from_monday() as usize;
+
+        match self.language {
+            Language::English => ENGLISH_NAMES[idx].to_string(),
+            Language::Japanese => format!("{}曜日", JAPANESE_NAMES[idx]),
+        }
+    }
+}
+
+impl PartialEq for DayOfWeek {
+    fn eq(&self, other: &Self) -> bool {
+        self.weekday == other.weekday
+    }
+}
+
+impl Eq for DayOfWeek {}
+
+impl std::hash::Hash for DayOfWeek {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.weekday.hash(state);
+    }
+}
+
+impl FromStr for DayOfWeek {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let name = name.trim();
+
+        if let Ok(weekday) = Weekday::from_str(name) {
+            return Ok(DayOfWeek::new(weekday, Language::English));
+        }
+
+        let stem = name
+            .strip_suffix("曜日")
+            .or_else(|| name.strip_suffix("曜"))
+            .unwrap_or(name);
+
+        JAPANESE_NAMES
+            .iter()
+            .position(|japanese_name| *japanese_name == stem)
+            .map(|idx| {
+                DayOfWeek::new(
+                    Weekday::try_from(idx as u8).expect("index is within a week"),
+                    Language::Japanese,
+                )
+            })
+            .ok_or_else(|| format!("unknown day of week \"{}\"", name))
+    }
+}
+
+impl fmt::Display for DayOfWeek {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.get_name())
+    }
+}
+
+impl<'de> Deserialize<'de> for DayOfWeek {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        DayOfWeek::from_str(&name).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for DayOfWeek {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.get_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_day_of_week() {
+        for name in ["Monday", "monday", "MON", "Mon", "月曜日", "月曜", "月"] {
+            assert_eq!(
+                DayOfWeek::from_str(name).unwrap().get_weekday(),
+                Weekday::Mon
+            );
+        }
+
+        assert_eq!(DayOfWeek::from_str("日曜").unwrap().to_string(), "日曜日");
+        assert!(DayOfWeek::from_str("Moonday").is_err());
+        assert!(DayOfWeek::from_str("月火").is_err());
+    }
+
+    #[test]
+    fn test_display_language() {
+        let mut day_of_week = DayOfWeek::from_str("thu").unwrap();
+        assert_eq!(day_of_week.to_string(), "Thursday");
+
+        day_of_week.set_language(Language::Japanese);
+        assert_eq!(day_of_week.to_string(), "木曜日");
+        assert_eq!(serde_json::to_string(&day_of_week).unwrap(), "\"木曜日\"");
+    }
+}
@@ -0,0 +1,191 @@
+use super::schedule::{Schedule, ScheduleRecord};
+use super::text;
+use super::validation::{Issue, Severity, ValidationReport};
+
+use std::path::Path;
+use std::str::FromStr;
+
+// The file formats a schedule can be written in. They all describe the same
+// records, so a schedule converts between them without losing anything; `Text`
+// is the line-oriented format of the `text` module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScheduleFormat {
+    Json,
+    Yaml,
+    Toml,
+    Text,
+}
+
+impl FromStr for ScheduleFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "json" => Ok(ScheduleFormat::Json),
+            "yaml" | "yml" => Ok(ScheduleFormat::Yaml),
+            "toml" => Ok(ScheduleFormat::Toml),
+            "txt" | "text" => Ok(ScheduleFormat::Text),
+            _ => Err(format!("unknown schedule format \"{}\"", s)),
+        }
+    }
+}
+
+impl ScheduleFormat {
+    // `None` for extensions that name no schedule format
+    pub fn from_path(file_path: &Path) -> Option<ScheduleFormat> {
+        file_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| extension.parse().ok())
+    }
+
+    pub fn parse(&self, contents: &str) -> Result<Schedule, ValidationReport> {
+        let record = match self {
+            ScheduleFormat::Json => return Schedule::parse(contents),
+            ScheduleFormat::Text => return text::parse_schedule(contents),
+            ScheduleFormat::Yaml => {
+                serde_norway::from_str::<ScheduleRecord>(contents).map_err(|err| err.to_string())
+            }
+            ScheduleFormat::Toml => toml::from_str::<toml::Value>(contents)
+                .and_then(|value| stringify_datetimes(value).try_into::<ScheduleRecord>())
+                .map_err(|err| err.to_string()),
+        };
+
+        match record {
+            Ok(record) => Schedule::from_record(record),
+            Err(err) => {
+                let mut report = ValidationReport::new();
+                report.push(Issue::new(Severity::Error, "", err.trim_end()));
+                Err(report)
+            }
+        }
+    }
+
+    pub fn write(&self, schedule: &Schedule) -> Result<String, String> {
+        match self {
+            ScheduleFormat::Json => serde_json::to_string_pretty(schedule)
+                .map(|contents| contents + "\n")
+                .map_err(|err| err.to_string()),
+            ScheduleFormat::Yaml => serde_norway::to_string(schedule).map_err(|err| err.to_string()),
+            ScheduleFormat::Toml => toml::to_string_pretty(schedule).map_err(|err| err.to_string()),
+            ScheduleFormat::Text => text::format_schedule(schedule),
+        }
+    }
+}
+
+// bare TOML dates and times like `start_date = 2026-11-02` are read the same
+// as quoted ones
+fn stringify_datetimes(value: toml::Value) -> toml::Value {
+    match value {
+        toml::Value::Datetime(datetime) => toml::Value::String(datetime.to_string()),
+        toml::Value::Array(values) => {
+            toml::Value::Array(values.into_iter().map(stringify_datetimes).collect())
+        }
+        toml::Value::Table(table) => toml::Value::Table(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, stringify_datetimes(value)))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEDULE: &str = r#"{
+        "days": [
+            { "day_of_week": "月", "tasks": [
+                { "id": "bento", "title": "お弁当準備", "start_time": "05:30", "end_time": "06:30", "details": "卵焼き\nおにぎり", "reminders": [10] },
+                { "title": "Night shift", "start_time": "23:00", "end_time": "07:00", "end_day_offset": 1, "silent": true, "details": "",
+                  "recurrence": { "rule": "FREQ=WEEKLY;INTERVAL=2", "start_date": "2026-11-02", "end_date": "2026-12-31" } }
+            ] },
+            { "day_of_week": "Friday", "tasks": [] }
+        ],
+        "overrides": {
+            "2026-10-26": { "cancel": ["bento"], "add": [
+                { "id": "offsite", "title": "Offsite", "start_time": "10:00", "end_time": "17:00", "details": "" }
+            ] }
+        },
+        "reminders": [5, 0],
+        "notify_end": true
+    }"#;
+
+    #[test]
+    fn test_round_trip() {
+        let schedule = ScheduleFormat::Json.parse(SCHEDULE).unwrap();
+        let expected = ScheduleFormat::Json.write(&schedule).unwrap();
+
+        for format in [
+            ScheduleFormat::Yaml,
+            ScheduleFormat::Toml,
+            ScheduleFormat::Text,
+        ] {
+            let contents = format.write(&schedule).unwrap();
+            let parsed = format.parse(&contents).unwrap();
+            assert_eq!(
+                ScheduleFormat::Json.write(&parsed).unwrap(),
+                expected,
+                "{:?}:\n{}",
+                format,
+                contents
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        let format_of = |path: &str| ScheduleFormat::from_path(Path::new(path));
+
+        assert_eq!(format_of("schedule.json"), Some(ScheduleFormat::Json));
+        assert_eq!(format_of("week.YML"), Some(ScheduleFormat::Yaml));
+        assert_eq!(
+            format_of("/etc/wesche/week.toml"),
+            Some(ScheduleFormat::Toml)
+        );
+        assert_eq!(format_of("week.txt"), Some(ScheduleFormat::Text));
+        assert_eq!(format_of("schedule"), None);
+    }
+
+    #[test]
+    fn test_toml_schedule() {
+        let schedule = ScheduleFormat::Toml
+            .parse(
+                r#"
+                # quoted and bare dates and times read the same
+                [[days]]
+                day_of_week = "Monday"
+
+                [[days.tasks]]
+                title = "Night shift"
+                start_time = 23:00:00
+                end_time = "07:00"
+                details = ""
+                recurrence = { rule = "FREQ=WEEKLY;INTERVAL=2", start_date = 2026-11-02 }
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            ScheduleFormat::Json.write(&schedule).unwrap(),
+            ScheduleFormat::Json
+                .write(
+                    &ScheduleFormat::Json
+                        .parse(
+                            r#"{ "days": [{ "day_of_week": "Monday", "tasks": [
+                                { "title": "Night shift", "start_time": "23:00", "end_time": "07:00", "details": "",
+                                  "recurrence": { "rule": "FREQ=WEEKLY;INTERVAL=2", "start_date": "2026-11-02" } }
+                            ] }] }"#
+                        )
+                        .unwrap()
+                )
+                .unwrap()
+        );
+
+        let report = ScheduleFormat::Toml
+            .parse("[[days]]\nday_of_week = \"Monday\"\ntasks = 3\n")
+            .unwrap_err();
+        assert!(report.has_errors());
+    }
+}
@@ -0,0 +1,228 @@
+use super::occurrence::Occurrence;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// task id and start of one occurrence
+pub type OccurrenceKey = (String, NaiveDateTime);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Pending,
+    InProgress,
+    Done,
+    Skipped,
+    Missed,
+}
+
+impl Status {
+    pub fn is_final(&self) -> bool {
+        matches!(self, Status::Done | Status::Skipped | Status::Missed)
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Status::Pending => "pending",
+            Status::InProgress => "in progress",
+            Status::Done => "done",
+            Status::Skipped => "skipped",
+            Status::Missed => "missed",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryEntry {
+    task_id: String,
+    title: String,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    status: Status,
+    updated_at: NaiveDateTime,
+}
+
+// what happened to each occurrence, kept in the history file
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct History {
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn get_recorded_status(&self, key: &OccurrenceKey) -> Option<Status> {
+        self.entries
+            .iter()
+            .find(|entry| entry.task_id == key.0 && entry.start == key.1)
+            .map(|entry| entry.status)
+    }
+
+    // in start order
+    pub fn get_entries_on(&self, date: NaiveDate) -> Vec<&HistoryEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.start.date() == date)
+            .collect()
+    }
+
+    // drops the entries of occurrences that started before `before`; returns
+    // whether anything was dropped
+    pub fn prune(&mut self, before: NaiveDateTime) -> bool {
+        let count = self.entries.len();
+        self.entries.retain(|entry| entry.start >= before);
+
+        self.entries.len() != count
+    }
+
+    // the recorded status, or the one implied by the time if nothing final
+    // was recorded
+    pub fn get_status(&self, occurrence: &Occurrence, now: NaiveDateTime) -> Status {
+        let key = (
+            occurrence.get_task().get_id().to_string(),
+            occurrence.get_start(),
+        );
+
+        match self.get_recorded_status(&key) {
+            Some(status) if status.is_final() => status,
+            _ if now < occurrence.get_start() => Status::Pending,
+            _ if now < occurrence.get_end() => Status::InProgress,
+            _ => Status::Missed,
+        }
+    }
+
+    // returns whether anything changed
+    pub fn set_status(
+        &mut self,
+        occurrence: &Occurrence,
+        status: Status,
+        updated_at: NaiveDateTime,
+    ) -> bool {
+        let task_id = occurrence.get_task().get_id();
+        let start = occurrence.get_start();
+
+        match self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.task_id == task_id && entry.start == start)
+        {
+            Some(entry) if entry.status == status => false,
+            Some(entry) => {
+                entry.status = status;
+                entry.updated_at = updated_at;
+                true
+            }
+            None => {
+                self.entries.push(HistoryEntry {
+                    task_id: task_id.to_string(),
+                    title: occurrence.get_task().get_title().to_string(),
+                    start,
+                    end: occurrence.get_end(),
+                    status,
+                    updated_at,
+                });
+                self.entries.sort_by_key(|entry| entry.start);
+                true
+            }
+        }
+    }
+}
+
+impl HistoryEntry {
+    pub fn get_title(&self) -> &str {
+        self.title.as_str()
+    }
+
+    pub fn get_start(&self) -> NaiveDateTime {
+        self.start
+    }
+
+    pub fn get_end(&self) -> NaiveDateTime {
+        self.end
+    }
+
+    pub fn get_status(&self) -> Status {
+        self.status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::day::Day;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_history_status() {
+        let day = serde_json::from_str::<Day>(
+            r#"{ "day_of_week": "Monday", "tasks": [
+                { "title": "Standup", "start_time": "09:00", "end_time": "09:15", "details": "" },
+                { "title": "Review", "start_time": "10:00", "end_time": "10:30", "details": "" }
+            ] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let at = |hour, minute| monday.and_hms_opt(hour, minute, 0).unwrap();
+        let standup = Occurrence::new(&day.get_tasks()[0], monday);
+        let review = Occurrence::new(&day.get_tasks()[1], monday);
+
+        let mut history = History::new();
+        assert_eq!(history.get_status(&standup, at(8, 0)), Status::Pending);
+        assert_eq!(history.get_status(&standup, at(9, 5)), Status::InProgress);
+        assert_eq!(history.get_status(&standup, at(9, 30)), Status::Missed);
+
+        assert!(history.set_status(&standup, Status::Done, at(9, 10)));
+        assert!(!history.set_status(&standup, Status::Done, at(9, 11)));
+        assert!(history.set_status(&review, Status::Skipped, at(9, 12)));
+
+        let reloaded =
+            serde_json::from_str::<History>(&serde_json::to_string(&history).unwrap()).unwrap();
+        assert_eq!(reloaded.get_status(&standup, at(12, 0)), Status::Done);
+        assert_eq!(reloaded.get_status(&review, at(8, 0)), Status::Skipped);
+    }
+
+    #[test]
+    fn test_prune_and_entries_on() {
+        let day = serde_json::from_str::<Day>(
+            r#"{ "day_of_week": "Monday", "tasks": [
+                { "id": "standup", "title": "Standup", "start_time": "09:00", "end_time": "09:15", "details": "" }
+            ] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let next_monday = monday + chrono::Duration::weeks(1);
+        let standup = Occurrence::new(&day.get_tasks()[0], monday);
+        let next_standup = Occurrence::new(&day.get_tasks()[0], next_monday);
+
+        let mut history = History::new();
+        history.set_status(
+            &standup,
+            Status::Done,
+            monday.and_hms_opt(9, 10, 0).unwrap(),
+        );
+        history.set_status(
+            &next_standup,
+            Status::Skipped,
+            next_monday.and_hms_opt(9, 10, 0).unwrap(),
+        );
+
+        let entries = history.get_entries_on(monday);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].get_title(), "Standup");
+        assert_eq!(entries[0].get_status(), Status::Done);
+
+        assert!(history.prune(next_monday.and_hms_opt(0, 0, 0).unwrap()));
+        assert!(!history.prune(next_monday.and_hms_opt(0, 0, 0).unwrap()));
+        assert!(history.get_entries_on(monday).is_empty());
+        assert_eq!(history.get_entries_on(next_monday).len(), 1);
+    }
+}
@@ -0,0 +1,1002 @@
+use super::day_of_week::{DayOfWeek, Language};
+use super::recurrence::{self, Frequency, Rule};
+use super::schedule::{Schedule, ScheduleRecord};
+use super::task::{self, Task};
+use super::validation::{Issue, Severity, ValidationReport};
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Weekday};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+
+const PRODUCT_ID: &str = "-//wesche//wesche//EN";
+
+const UID_DOMAIN: &str = "wesche";
+
+// how far past the start the first occurrence of a task is looked for
+const FIRST_OCCURRENCE_SEARCH_DAYS: usize = 2 * 366;
+
+// RFC 5545 lines are folded after this many octets
+const MAX_LINE_OCTETS: usize = 75;
+
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+// every weekly task becomes a recurring VEVENT starting at its first
+// occurrence on or after `from` (or its recurrence start date), in floating
+// local time; tasks added by date overrides become one-off VEVENTs and the
+// occurrences they replace or cancel become EXDATEs
+pub fn export_schedule(schedule: &Schedule, from: NaiveDate, stamp: NaiveDateTime) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for task in schedule.get_days().iter().flat_map(|day| day.get_tasks()) {
+        let search_from = task
+            .get_recurrence()
+            .and_then(|recurrence| recurrence.get_start_date())
+            .unwrap_or(from);
+        let Some(first_date) = search_from
+            .iter_days()
+            .take(FIRST_OCCURRENCE_SEARCH_DAYS)
+            .find(|date| task.occurs_on(*date))
+        else {
+            continue;
+        };
+
+        let mut properties = vec![format!(
+            "RRULE:{}",
+            recurrence::to_ical_rule(task.get_recurrence(), task.get_weekday())
+        )];
+        let excluded_dates = schedule
+            .get_overrides()
+            .iter()
+            .filter(|(date, date_override)| {
+                **date >= first_date && task.occurs_on(**date) && date_override.is_removed(task)
+            })
+            .map(|(date, _)| format_date_time(date.and_time(task.get_start_time())))
+            .collect::<Vec<_>>();
+        if !excluded_dates.is_empty() {
+            properties.push(format!("EXDATE:{}", excluded_dates.join(",")));
+        }
+
+        lines.extend(build_event(
+            task,
+            first_date,
+            &format!("{}@{}", task.get_id(), UID_DOMAIN),
+            stamp,
+            properties,
+        ));
+    }
+
+    for (date, date_override) in schedule.get_overrides() {
+        for task in date_override.get_added_tasks() {
+            lines.extend(build_event(
+                task,
+                *date,
+                &format!("{}-{}@{}", task.get_id(), date.format("%Y%m%d"), UID_DOMAIN),
+                stamp,
+                Vec::new(),
+            ));
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| fold_line(line))
+        .collect::<Vec<_>>()
+        .concat()
+}
+
+// `stamp` goes into DTSTAMP and has to be in UTC
+fn build_event(
+    task: &Task,
+    date: NaiveDate,
+    uid: &str,
+    stamp: NaiveDateTime,
+    properties: Vec<String>,
+) -> Vec<String> {
+    let start = date.and_time(task.get_start_time());
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", uid),
+        format!("DTSTAMP:{}Z", stamp.format(DATE_TIME_FORMAT)),
+        format!("DTSTART:{}", format_date_time(start)),
+        format!("DTEND:{}", format_date_time(start + task.get_duration())),
+        format!("SUMMARY:{}", escape_text(task.get_title())),
+    ];
+    if !task.get_details().is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape_text(task.get_details())));
+    }
+    lines.extend(properties);
+    lines.push("END:VEVENT".to_string());
+
+    lines
+}
+
+fn format_date_time(date_time: NaiveDateTime) -> String {
+    date_time.format(DATE_TIME_FORMAT).to_string()
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// splits the line into CRLF-terminated chunks of at most 75 octets, without
+// breaking a UTF-8 character; continuation lines start with a space
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut octets = 0;
+
+    for character in line.chars() {
+        if octets + character.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(character);
+        octets += character.len_utf8();
+    }
+    folded.push_str("\r\n");
+
+    folded
+}
+
+// A VEVENT reduced to what a task can hold.
+struct Event {
+    uid: String,
+    title: String,
+    details: String,
+    start: NaiveDateTime,
+    // days between the UTC and the local start date of a UTC start
+    start_day_shift: i64,
+    end: NaiveDateTime,
+    rule: Option<String>,
+    excluded_dates: Vec<NaiveDate>,
+    // the occurrence of a recurring event this one replaces
+    recurrence_id: Option<NaiveDate>,
+}
+
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+// Recurring events whose rule wesche can express become weekly tasks, listed
+// under every BYDAY weekday when the rule is a plain weekly one; other events
+// become tasks added by date overrides, and EXDATEs become cancellations.
+// Events that can't be imported are reported as warnings and left out, while
+// a calendar that doesn't parse or a schedule that doesn't validate is an
+// error. `today` is the import date, `time_zone` the IANA name of the local
+// time zone TZID times are read in, and `local` the zone UTC times are
+// converted to.
+pub fn import_calendar<Tz: TimeZone>(
+    contents: &str,
+    today: NaiveDate,
+    time_zone: &str,
+    local: &Tz,
+) -> Result<(Schedule, ValidationReport), ValidationReport> {
+    let mut report = ValidationReport::new();
+
+    let components = match parse_components(contents) {
+        Ok(components) => components,
+        Err(err) => {
+            report.push(Issue::new(Severity::Error, "", &err));
+            return Err(report);
+        }
+    };
+
+    let mut events = Vec::new();
+    for (idx, properties) in components.iter().enumerate() {
+        let location = match find_property(properties, "UID") {
+            Some(uid) => format!("VEVENT {}", uid.value),
+            None => format!("VEVENT #{}", idx),
+        };
+
+        match parse_event(properties, local) {
+            Ok(Some(event)) => {
+                if let Some(tzid) = find_foreign_time_zone(properties, time_zone) {
+                    report.push(Issue::new(
+                        Severity::Warning,
+                        &location,
+                        &format!(
+                            "TZID={} is not the local time zone {}; times are read as local",
+                            tzid, time_zone
+                        ),
+                    ));
+                }
+                events.push(event);
+            }
+            Ok(None) => (),
+            Err(err) => report.push(Issue::new(
+                Severity::Warning,
+                &location,
+                &format!("{}; skipped", err),
+            )),
+        }
+    }
+
+    let mut days: [Vec<Value>; 7] = Default::default();
+    let mut overrides: BTreeMap<NaiveDate, (Vec<String>, Vec<Value>)> = BTreeMap::new();
+    // the task ids of every recurring event, by the weekday they are listed under
+    let mut series_ids: HashMap<String, Vec<(Weekday, String)>> = HashMap::new();
+
+    for event in events.iter() {
+        let Some(rule) = &event.rule else {
+            continue;
+        };
+        let (weekdays, recurrence) =
+            match plan_series(rule, event.start.date(), event.start_day_shift, today) {
+                Ok(plan) => plan,
+                Err(err) => {
+                    report.push(Issue::new(
+                        Severity::Warning,
+                        &format!("VEVENT {}", event.uid),
+                        &format!("{}; skipped", err),
+                    ));
+                    continue;
+                }
+            };
+
+        let base_id = get_task_id(&event.uid, None);
+        let mut ids = Vec::new();
+        for weekday in weekdays.iter() {
+            let id = if weekdays.len() > 1 {
+                format!("{}-{}", base_id, weekday.to_string().to_lowercase())
+            } else {
+                base_id.clone()
+            };
+            days[weekday.num_days_from_monday() as usize].push(build_task_record(
+                event,
+                &id,
+                recurrence.clone(),
+            ));
+            ids.push((*weekday, id));
+        }
+        for date in event.excluded_dates.iter() {
+            overrides
+                .entry(*date)
+                .or_default()
+                .0
+                .push(find_series_id(&ids, *date));
+        }
+        series_ids.insert(event.uid.clone(), ids);
+    }
+
+    for event in events.iter().filter(|event| event.rule.is_none()) {
+        let date = event.start.date();
+        let series = series_ids.get(&event.uid);
+
+        let id = match (event.recurrence_id, series) {
+            (Some(recurrence_id), Some(ids)) => {
+                let id = find_series_id(ids, recurrence_id);
+                if recurrence_id != date {
+                    overrides
+                        .entry(recurrence_id)
+                        .or_default()
+                        .0
+                        .push(id.clone());
+                }
+                id
+            }
+            _ => get_task_id(&event.uid, Some(date)),
+        };
+        overrides
+            .entry(date)
+            .or_default()
+            .1
+            .push(build_task_record(event, &id, None));
+    }
+
+    let record = json!({
+        "days": days
+            .into_iter()
+            .enumerate()
+            .filter(|(_, tasks)| !tasks.is_empty())
+            .map(|(idx, tasks)| {
+                let weekday = Weekday::try_from(idx as u8).unwrap();
+                json!({
+                    "day_of_week": DayOfWeek::new(weekday, Language::English).to_string(),
+                    "tasks": tasks,
+                })
+            })
+            .collect::<Vec<_>>(),
+        "overrides": overrides
+            .into_iter()
+            .map(|(date, (cancel, add))| {
+                (date.to_string(), json!({ "cancel": cancel, "add": add }))
+            })
+            .collect::<serde_json::Map<_, _>>(),
+    });
+
+    let record = match serde_json::from_value::<ScheduleRecord>(record) {
+        Ok(record) => record,
+        Err(err) => {
+            report.push(Issue::new(Severity::Error, "", &err.to_string()));
+            return Err(report);
+        }
+    };
+    match Schedule::from_record(record) {
+        Ok(schedule) => {
+            report.extend(schedule.validate().get_issues().iter().cloned());
+            Ok((schedule, report))
+        }
+        Err(errors) => {
+            report.extend(errors.get_issues().iter().cloned());
+            Err(report)
+        }
+    }
+}
+
+// the weekdays to list the task under and its recurrence, if the template
+// alone doesn't describe it; plain weekly rules keep only their end date, and
+// their start date when occurrences between `today` and it would show up
+fn plan_series(
+    rule: &str,
+    start_date: NaiveDate,
+    start_day_shift: i64,
+    today: NaiveDate,
+) -> Result<(Vec<Weekday>, Option<Value>), String> {
+    let mut until = None;
+    let mut parts = Vec::new();
+    for part in rule.split(';').filter(|part| !part.is_empty()) {
+        match part.split_once('=') {
+            Some((key, value)) if key.eq_ignore_ascii_case("UNTIL") => {
+                let date = value.get(..8).unwrap_or(value);
+                until = Some(
+                    NaiveDate::parse_from_str(date, "%Y%m%d")
+                        .map_err(|_| format!("invalid UNTIL={}", value))?,
+                );
+            }
+            // weeks start on Monday in wesche too
+            Some((key, value))
+                if key.eq_ignore_ascii_case("WKST") && value.eq_ignore_ascii_case("MO") => {}
+            _ => parts.push(part),
+        }
+    }
+
+    let parsed = parts
+        .join(";")
+        .parse::<Rule>()
+        .and_then(|parsed| parsed.shift_weekdays(start_day_shift))
+        .map_err(|err| format!("unsupported RRULE \"{}\": {}", rule, err))?;
+
+    if parsed.get_frequency() == Frequency::Weekly && parsed.get_interval() == 1 {
+        let mut weekdays = parsed.get_weekdays();
+        if weekdays.is_empty() {
+            weekdays.push(start_date.weekday());
+        }
+
+        let mut recurrence = serde_json::Map::new();
+        if today
+            .iter_days()
+            .take_while(|date| *date < start_date)
+            .any(|date| weekdays.contains(&date.weekday()))
+        {
+            recurrence.insert("start_date".to_string(), json!(start_date));
+        }
+        if let Some(until) = until {
+            recurrence.insert("end_date".to_string(), json!(until));
+        }
+        return Ok((
+            weekdays,
+            (!recurrence.is_empty()).then_some(Value::Object(recurrence)),
+        ));
+    }
+
+    Ok((
+        vec![start_date.weekday()],
+        Some(json!({
+            "rule": parsed.to_string(),
+            "start_date": start_date,
+            "end_date": until,
+        })),
+    ))
+}
+
+fn build_task_record(event: &Event, id: &str, recurrence: Option<Value>) -> Value {
+    let mut record = json!({
+        "id": id,
+        "title": event.title,
+        "start_time": task::format_time(&event.start.time()),
+        "end_time": task::format_time(&event.end.time()),
+        "details": event.details,
+    });
+
+    // an end time before the start time already implies the next day
+    let day_offset = (event.end.date() - event.start.date()).num_days();
+    let implied_day_offset = i64::from(event.end.time() < event.start.time());
+    if day_offset != implied_day_offset {
+        record["end_day_offset"] = json!(day_offset);
+    }
+    if let Some(recurrence) = recurrence {
+        record["recurrence"] = recurrence;
+    }
+
+    record
+}
+
+// UIDs written by `export_schedule` turn back into the task ids they came from
+fn get_task_id(uid: &str, date: Option<NaiveDate>) -> String {
+    let Some(id) = uid.strip_suffix(&format!("@{}", UID_DOMAIN)) else {
+        return uid.to_string();
+    };
+
+    match date {
+        Some(date) => id
+            .strip_suffix(&format!("-{}", date.format("%Y%m%d")))
+            .unwrap_or(id)
+            .to_string(),
+        None => id.to_string(),
+    }
+}
+
+// the first TZID of the event's times that isn't `time_zone`
+fn find_foreign_time_zone<'a>(properties: &'a [Property], time_zone: &str) -> Option<&'a str> {
+    properties
+        .iter()
+        .filter(|property| {
+            matches!(
+                property.name.as_str(),
+                "DTSTART" | "DTEND" | "EXDATE" | "RECURRENCE-ID"
+            )
+        })
+        .filter_map(|property| property.get_param("TZID"))
+        .find(|tzid| *tzid != time_zone)
+}
+
+fn find_series_id(ids: &[(Weekday, String)], date: NaiveDate) -> String {
+    ids.iter()
+        .find(|(weekday, _)| *weekday == date.weekday())
+        .or(ids.first())
+        .map(|(_, id)| id.clone())
+        .unwrap_or_default()
+}
+
+// `None` for cancelled events
+fn parse_event<Tz: TimeZone>(properties: &[Property], local: &Tz) -> Result<Option<Event>, String> {
+    if find_property(properties, "STATUS").is_some_and(|status| status.value == "CANCELLED") {
+        return Ok(None);
+    }
+
+    let uid = find_property(properties, "UID").ok_or("missing UID")?;
+    let start_property = find_property(properties, "DTSTART").ok_or("missing DTSTART")?;
+    let start = parse_date_time(start_property, local)?;
+    // BYDAY weekdays are those of the UTC start
+    let start_day_shift = match start_property.value.trim().strip_suffix('Z') {
+        Some(value) => NaiveDateTime::parse_from_str(value, DATE_TIME_FORMAT)
+            .map(|utc_start| (start.date() - utc_start.date()).num_days())
+            .unwrap_or(0),
+        None => 0,
+    };
+    let end = match (
+        find_property(properties, "DTEND"),
+        find_property(properties, "DURATION"),
+    ) {
+        (Some(end), _) => parse_date_time(end, local)?,
+        (None, Some(duration)) => {
+            start
+                + parse_duration(&duration.value)
+                    .ok_or_else(|| format!("invalid DURATION:{}", duration.value))?
+        }
+        (None, None) => start,
+    };
+    if end < start {
+        return Err("DTEND is before DTSTART".to_string());
+    }
+
+    let rules = properties
+        .iter()
+        .filter(|property| property.name == "RRULE")
+        .collect::<Vec<_>>();
+    if rules.len() > 1 {
+        return Err("more than one RRULE is not supported".to_string());
+    }
+    if find_property(properties, "RDATE").is_some() {
+        return Err("RDATE is not supported".to_string());
+    }
+
+    let mut excluded_dates = Vec::new();
+    for property in properties
+        .iter()
+        .filter(|property| property.name == "EXDATE")
+    {
+        for value in property.value.split(',') {
+            excluded_dates.push(parse_date(value, property, local)?);
+        }
+    }
+    let recurrence_id = find_property(properties, "RECURRENCE-ID")
+        .map(|property| parse_date(&property.value, property, local))
+        .transpose()?;
+
+    Ok(Some(Event {
+        uid: uid.value.clone(),
+        title: find_property(properties, "SUMMARY")
+            .map(|summary| unescape_text(&summary.value))
+            .unwrap_or_default(),
+        details: find_property(properties, "DESCRIPTION")
+            .map(|description| unescape_text(&description.value))
+            .unwrap_or_default(),
+        start,
+        start_day_shift,
+        end,
+        rule: rules.first().map(|rule| rule.value.clone()),
+        excluded_dates,
+        recurrence_id,
+    }))
+}
+
+// floating and TZID times are taken as local wall-clock times, UTC times are
+// converted to the local time zone
+fn parse_date_time<Tz: TimeZone>(property: &Property, local: &Tz) -> Result<NaiveDateTime, String> {
+    let value = property.value.trim();
+    if property.get_param("VALUE") == Some("DATE") || value.len() == 8 {
+        return Err("all-day events are not supported".to_string());
+    }
+
+    let (value, is_utc) = match value.strip_suffix('Z') {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let date_time = NaiveDateTime::parse_from_str(value, DATE_TIME_FORMAT)
+        .map_err(|_| format!("invalid {}:{}", property.name, property.value))?;
+
+    Ok(if is_utc {
+        local.from_utc_datetime(&date_time).naive_local()
+    } else {
+        date_time
+    })
+}
+
+// the date of an EXDATE or RECURRENCE-ID value, which may be a date-time
+fn parse_date<Tz: TimeZone>(
+    value: &str,
+    property: &Property,
+    local: &Tz,
+) -> Result<NaiveDate, String> {
+    let value = value.trim();
+    if value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map_err(|_| format!("invalid {}:{}", property.name, value));
+    }
+
+    parse_date_time(
+        &Property {
+            name: property.name.clone(),
+            params: Vec::new(),
+            value: value.to_string(),
+        },
+        local,
+    )
+    .map(|date_time| date_time.date())
+}
+
+// RFC 5545 durations such as "PT1H30M" or "P1DT2H"
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let value = value.strip_prefix('+').unwrap_or(value);
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+
+    for character in value.strip_prefix('P')?.chars() {
+        match character {
+            '0'..='9' => number.push(character),
+            'T' if number.is_empty() => (),
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let amount = number.parse::<i64>().ok()?;
+                number.clear();
+                duration += match character {
+                    'W' => Duration::weeks(amount),
+                    'D' => Duration::days(amount),
+                    'H' => Duration::hours(amount),
+                    'M' => Duration::minutes(amount),
+                    _ => Duration::seconds(amount),
+                };
+            }
+            _ => return None,
+        }
+    }
+
+    number.is_empty().then_some(duration)
+}
+
+// the properties of every VEVENT, leaving out nested components like VALARM
+fn parse_components(contents: &str) -> Result<Vec<Vec<Property>>, String> {
+    let lines = unfold_lines(contents);
+    if !lines
+        .iter()
+        .any(|line| line.eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+    {
+        return Err("not an iCalendar file (no BEGIN:VCALENDAR)".to_string());
+    }
+
+    let mut components = Vec::new();
+    let mut event: Option<Vec<Property>> = None;
+    let mut nested_depth = 0;
+
+    for (idx, line) in lines.iter().enumerate() {
+        let property = parse_property(line)
+            .ok_or_else(|| format!("line {}: malformed \"{}\"", idx + 1, line))?;
+
+        match (property.name.as_str(), property.value.as_str(), &mut event) {
+            ("BEGIN", "VEVENT", None) => event = Some(Vec::new()),
+            ("END", "VEVENT", Some(_)) if nested_depth == 0 => {
+                components.extend(event.take());
+            }
+            ("BEGIN", _, Some(_)) => nested_depth += 1,
+            ("END", _, Some(_)) => nested_depth -= 1,
+            (_, _, Some(properties)) if nested_depth == 0 => properties.push(property),
+            _ => (),
+        }
+    }
+
+    Ok(components)
+}
+
+// joins folded lines back together; lines may end with CRLF or just LF
+fn unfold_lines(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in contents.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if line.trim().is_empty() => (),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    lines
+}
+
+// NAME;PARAM=value;PARAM="quoted:value":VALUE
+fn parse_property(line: &str) -> Option<Property> {
+    let mut is_quoted = false;
+    let colon = line.char_indices().find_map(|(idx, character)| {
+        match character {
+            '"' => is_quoted = !is_quoted,
+            ':' if !is_quoted => return Some(idx),
+            _ => (),
+        }
+        None
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| {
+            (
+                key.trim().to_uppercase(),
+                value.trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+
+    Some(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+impl Property {
+    fn get_param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn find_property<'a>(properties: &'a [Property], name: &str) -> Option<&'a Property> {
+    properties.iter().find(|property| property.name == name)
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut characters = text.chars();
+
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            unescaped.push(character);
+            continue;
+        }
+        match characters.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(escaped) => unescaped.push(escaped),
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::FixedOffset;
+
+    fn tokyo() -> FixedOffset {
+        FixedOffset::east_opt(9 * 3600).unwrap()
+    }
+
+    #[test]
+    fn test_export_schedule() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{
+                "days": [
+                    { "day_of_week": "Monday", "tasks": [
+                        { "id": "standup", "title": "Standup, daily", "start_time": "09:00", "end_time": "09:15", "details": "Room 3\nBring notes" },
+                        { "id": "night", "title": "Night shift", "start_time": "23:00", "end_time": "07:00", "details": "",
+                          "recurrence": { "rule": "FREQ=WEEKLY;INTERVAL=2", "start_date": "2026-11-02", "end_date": "2026-12-31" } }
+                    ] }
+                ],
+                "overrides": {
+                    "2026-10-26": { "cancel": ["standup"], "add": [
+                        { "id": "offsite", "title": "Offsite", "start_time": "10:00", "end_time": "17:00", "details": "" }
+                    ] }
+                }
+            }"#,
+        )
+        .unwrap();
+        let from = NaiveDate::from_ymd_opt(2026, 10, 21).unwrap();
+        let stamp = from.and_hms_opt(8, 0, 0).unwrap();
+
+        let exported = export_schedule(&schedule, from, stamp);
+        assert!(exported.ends_with("END:VCALENDAR\r\n"));
+        let lines = exported.split("\r\n").collect::<Vec<_>>();
+
+        let event = |uid: &str| {
+            let start = lines
+                .iter()
+                .position(|line| *line == format!("UID:{}", uid))
+                .unwrap();
+            let end = start
+                + lines[start..]
+                    .iter()
+                    .position(|line| *line == "END:VEVENT")
+                    .unwrap();
+            lines[start + 1..end].to_vec()
+        };
+
+        assert_eq!(
+            event("standup@wesche"),
+            vec![
+                "DTSTAMP:20261021T080000Z",
+                "DTSTART:20261026T090000",
+                "DTEND:20261026T091500",
+                "SUMMARY:Standup\\, daily",
+                "DESCRIPTION:Room 3\\nBring notes",
+                "RRULE:FREQ=WEEKLY;BYDAY=MO",
+                "EXDATE:20261026T090000",
+            ]
+        );
+        assert_eq!(
+            event("night@wesche")[1..4],
+            [
+                "DTSTART:20261102T230000",
+                "DTEND:20261103T070000",
+                "SUMMARY:Night shift",
+            ]
+        );
+        assert_eq!(
+            event("night@wesche")[4],
+            "RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO;UNTIL=20261231T235959"
+        );
+        assert_eq!(
+            event("offsite-20261026@wesche")[1..3],
+            ["DTSTART:20261026T100000", "DTEND:20261026T170000"]
+        );
+    }
+
+    #[test]
+    fn test_import_exported_schedule() {
+        let json = r#"{
+            "days": [
+                { "day_of_week": "Monday", "tasks": [
+                    { "id": "standup", "title": "Standup, daily", "start_time": "09:00", "end_time": "09:15", "details": "Room 3\nBring notes" },
+                    { "id": "night", "title": "Night shift", "start_time": "23:00", "end_time": "07:00", "details": "",
+                      "recurrence": { "rule": "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO", "start_date": "2026-11-02", "end_date": "2026-12-31" } }
+                ] }
+            ],
+            "overrides": {
+                "2026-10-26": { "cancel": ["standup"], "add": [
+                    { "id": "offsite", "title": "Offsite", "start_time": "10:00", "end_time": "17:00", "details": "" }
+                ] }
+            }
+        }"#;
+        let schedule = serde_json::from_str::<Schedule>(json).unwrap();
+        let from = NaiveDate::from_ymd_opt(2026, 10, 21).unwrap();
+        let exported = export_schedule(&schedule, from, from.and_hms_opt(8, 0, 0).unwrap());
+
+        let (imported, report) = import_calendar(&exported, from, "Asia/Tokyo", &tokyo()).unwrap();
+        assert!(report.is_empty(), "{}", report);
+        assert_eq!(
+            serde_json::to_value(&imported).unwrap(),
+            serde_json::to_value(&schedule).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_import_calendar() {
+        let calendar = [
+            "BEGIN:VCALENDAR",
+            "VERSION:2.0",
+            "BEGIN:VEVENT",
+            "UID:gym-1",
+            "DTSTART;TZID=Asia/Tokyo:20261019T190000",
+            "DURATION:PT1H30M",
+            "SUMMARY:Gym",
+            "RRULE:FREQ=WEEKLY;BYDAY=MO,TH;WKST=MO",
+            "EXDATE;TZID=Asia/Tokyo:20261022T190000",
+            "BEGIN:VALARM",
+            "ACTION:DISPLAY",
+            "DESCRIPTION:Reminder",
+            "TRIGGER:-PT10M",
+            "END:VALARM",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:gym-1",
+            "RECURRENCE-ID;TZID=Asia/Tokyo:20261026T190000",
+            "DTSTART;TZID=Asia/Tokyo:20261027T080000",
+            "DTEND;TZID=Asia/Tokyo:20261027T090000",
+            "SUMMARY:Gym (moved)",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:rent",
+            "DTSTART:20261101T090000",
+            "DTEND:20261101T091000",
+            "SUMMARY:Pay rent",
+            "RRULE:FREQ=MONTHLY;BYMONTHDAY=1",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:dentist",
+            "DTSTART:20261030T153000",
+            "DTEND:20261030T163000",
+            "SUMMARY:Dentist\\; bring card",
+            "DESCRIPTION:Line one\\nline two wraps",
+            "  here",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:count",
+            "DTSTART:20261019T070000",
+            "DTEND:20261019T073000",
+            "SUMMARY:Stretch",
+            "RRULE:FREQ=DAILY;COUNT=10",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:yoga",
+            "DTSTART;TZID=America/New_York:20261105T180000",
+            "DTEND;TZID=America/New_York:20261105T190000",
+            "SUMMARY:Yoga",
+            "RRULE:FREQ=WEEKLY;BYDAY=TH",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:holiday",
+            "DTSTART;VALUE=DATE:20261103",
+            "SUMMARY:Holiday",
+            "END:VEVENT",
+            "END:VCALENDAR",
+        ]
+        .join("\r\n");
+
+        let today = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let (schedule, report) = import_calendar(&calendar, today, "Asia/Tokyo", &tokyo()).unwrap();
+        assert_eq!(
+            report.to_string(),
+            [
+                "warning: VEVENT yoga: TZID=America/New_York is not the local time zone Asia/Tokyo; times are read as local",
+                "warning: VEVENT holiday: all-day events are not supported; skipped",
+                "warning: VEVENT count: unsupported RRULE \"FREQ=DAILY;COUNT=10\": unsupported COUNT=10; skipped",
+            ]
+            .join("\n")
+        );
+
+        let date = |day: u32| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+        let titles_on = |date: NaiveDate| {
+            schedule
+                .get_occurrences_on(date)
+                .iter()
+                .map(|occurrence| {
+                    format!(
+                        "{} {}",
+                        occurrence.get_start().format("%H:%M"),
+                        occurrence.get_task().get_title()
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(titles_on(date(19)), ["19:00 Gym"]);
+        assert!(titles_on(date(22)).is_empty());
+        assert!(titles_on(date(26)).is_empty());
+        assert_eq!(titles_on(date(27)), ["08:00 Gym (moved)"]);
+        assert_eq!(titles_on(date(29)), ["19:00 Gym"]);
+        assert_eq!(titles_on(date(30)), ["15:30 Dentist; bring card"]);
+        assert_eq!(
+            titles_on(NaiveDate::from_ymd_opt(2026, 11, 5).unwrap()),
+            ["18:00 Yoga", "19:00 Gym"]
+        );
+        assert_eq!(
+            titles_on(NaiveDate::from_ymd_opt(2026, 12, 1).unwrap()),
+            ["09:00 Pay rent"]
+        );
+
+        let dentist = schedule.get_overrides()[&date(30)].get_added_tasks()[0].clone();
+        assert_eq!(dentist.get_details(), "Line one\nline two wraps here");
+        assert_eq!(
+            schedule.get_occurrences_on(date(19))[0].get_end(),
+            date(19).and_hms_opt(20, 30, 0).unwrap()
+        );
+
+        assert!(import_calendar(
+            "BEGIN:VEVENT\r\nEND:VEVENT\r\n",
+            today,
+            "Asia/Tokyo",
+            &tokyo()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_import_utc_event_crossing_midnight() {
+        let calendar = [
+            "BEGIN:VCALENDAR",
+            "BEGIN:VEVENT",
+            "UID:call",
+            "DTSTART:20261019T233000Z",
+            "DTEND:20261020T000000Z",
+            "SUMMARY:Call",
+            "RRULE:FREQ=WEEKLY;BYDAY=MO,FR",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:payday",
+            "DTSTART:20261019T233000Z",
+            "DTEND:20261020T000000Z",
+            "SUMMARY:Payday",
+            "RRULE:FREQ=MONTHLY;BYDAY=-1MO",
+            "END:VEVENT",
+            "END:VCALENDAR",
+        ]
+        .join("\r\n");
+
+        let today = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let (schedule, report) = import_calendar(&calendar, today, "Asia/Tokyo", &tokyo()).unwrap();
+        assert_eq!(
+            report.to_string(),
+            "warning: VEVENT payday: unsupported RRULE \"FREQ=MONTHLY;BYDAY=-1MO\": BYDAY ordinals and BYMONTHDAY can't be moved to the local day; skipped"
+        );
+
+        let starts = today
+            .iter_days()
+            .take(7)
+            .flat_map(|date| schedule.get_occurrences_on(date))
+            .map(|occurrence| occurrence.get_start().format("%a %H:%M").to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(starts, ["Tue 08:30", "Sat 08:30"]);
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let line = format!("SUMMARY:{}", "お弁当準備".repeat(6));
+        let folded = fold_line(&line);
+
+        assert!(folded
+            .split("\r\n")
+            .all(|chunk| chunk.len() <= MAX_LINE_OCTETS));
+        assert_eq!(folded.replace("\r\n ", "").trim_end(), line);
+    }
+}
@@ -1,3 +1,14 @@
+pub mod date_override;
 pub mod day;
+pub mod day_of_week;
+pub mod format;
+pub mod history;
+pub mod ical;
+pub mod notification;
+pub mod occurrence;
+pub mod quiet_hours;
+pub mod recurrence;
 pub mod schedule;
 pub mod task;
+pub mod text;
+pub mod validation;
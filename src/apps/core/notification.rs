@@ -0,0 +1,123 @@
+use chrono::NaiveDateTime;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NotificationKind {
+    Reminder { minutes_before: u32 },
+    Start,
+    EndReminder { minutes_before: u32 },
+    End,
+}
+
+// A notification of one occurrence that may be shown from `fire_at` until
+// `expires_at`; once expired it is dropped instead of being shown late.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlannedNotification {
+    kind: NotificationKind,
+    fire_at: NaiveDateTime,
+    expires_at: NaiveDateTime,
+}
+
+impl PlannedNotification {
+    pub fn new(
+        kind: NotificationKind,
+        fire_at: NaiveDateTime,
+        expires_at: NaiveDateTime,
+    ) -> PlannedNotification {
+        PlannedNotification {
+            kind,
+            fire_at,
+            expires_at,
+        }
+    }
+
+    pub fn get_kind(&self) -> NotificationKind {
+        self.kind
+    }
+
+    pub fn get_fire_at(&self) -> NaiveDateTime {
+        self.fire_at
+    }
+
+    pub fn is_due(&self, date_time: NaiveDateTime) -> bool {
+        self.fire_at <= date_time && date_time <= self.expires_at
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationAction {
+    Snooze { minutes: u32 },
+    Done,
+    Skip,
+}
+
+pub const TASK_ACTIONS: [NotificationAction; 4] = [
+    NotificationAction::Snooze { minutes: 5 },
+    NotificationAction::Snooze { minutes: 15 },
+    NotificationAction::Done,
+    NotificationAction::Skip,
+];
+
+impl NotificationAction {
+    pub fn get_id(&self) -> String {
+        match self {
+            NotificationAction::Snooze { minutes } => format!("snooze-{}", minutes),
+            NotificationAction::Done => "done".to_string(),
+            NotificationAction::Skip => "skip".to_string(),
+        }
+    }
+
+    pub fn get_label(&self) -> String {
+        match self {
+            NotificationAction::Snooze { minutes } => format!("Snooze {} min", minutes),
+            NotificationAction::Done => "Done".to_string(),
+            NotificationAction::Skip => "Skip today".to_string(),
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<NotificationAction> {
+        match id {
+            "done" => Some(NotificationAction::Done),
+            "skip" => Some(NotificationAction::Skip),
+            id => id
+                .strip_prefix("snooze-")
+                .and_then(|minutes| minutes.parse().ok())
+                .map(|minutes| NotificationAction::Snooze { minutes }),
+        }
+    }
+}
+
+// what to do with notifications missed while asleep or not running
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CatchUpPolicy {
+    #[default]
+    Summarize,
+    ReplayAll,
+    Ignore,
+}
+
+impl FromStr for CatchUpPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "summarize" => Ok(CatchUpPolicy::Summarize),
+            "replay_all" | "replay" => Ok(CatchUpPolicy::ReplayAll),
+            "ignore" => Ok(CatchUpPolicy::Ignore),
+            _ => Err(format!("unknown catch-up policy \"{}\"", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_ids_round_trip() {
+        for action in TASK_ACTIONS {
+            assert_eq!(NotificationAction::from_id(&action.get_id()), Some(action));
+        }
+        assert_eq!(NotificationAction::from_id("__closed"), None);
+    }
+}
@@ -0,0 +1,195 @@
+use super::notification::{NotificationKind, PlannedNotification};
+use super::task::Task;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+// how long after the end a missed "ended" notification is still worth showing
+pub const END_NOTIFICATION_GRACE_MINUTES: i64 = 1;
+
+#[derive(Clone, Debug)]
+pub struct Occurrence<'a> {
+    task: &'a Task,
+    date: NaiveDate,
+}
+
+impl<'a> Occurrence<'a> {
+    pub fn new(task: &'a Task, date: NaiveDate) -> Occurrence<'a> {
+        Occurrence { task, date }
+    }
+
+    pub fn get_task(&self) -> &'a Task {
+        self.task
+    }
+
+    pub fn get_start(&self) -> NaiveDateTime {
+        self.date.and_time(self.task.get_start_time())
+    }
+
+    pub fn get_end(&self) -> NaiveDateTime {
+        (self.date + Duration::days(self.task.get_end_day_offset().into()))
+            .and_time(self.task.get_end_time())
+    }
+
+    // a reminder is only useful until the task starts, where the start
+    // notification takes over
+    pub fn plan_notifications(&self, reminder_minutes: &[u32]) -> Vec<PlannedNotification> {
+        let start = self.get_start();
+
+        let mut notifications = reminder_minutes
+            .iter()
+            .filter(|minutes_before| **minutes_before > 0)
+            .map(|minutes_before| {
+                PlannedNotification::new(
+                    NotificationKind::Reminder {
+                        minutes_before: *minutes_before,
+                    },
+                    start - Duration::minutes((*minutes_before).into()),
+                    start - Duration::seconds(1),
+                )
+            })
+            .collect::<Vec<_>>();
+        notifications.push(PlannedNotification::new(
+            NotificationKind::Start,
+            start,
+            self.get_end(),
+        ));
+
+        notifications
+    }
+
+    // an end reminder that would fire before the task starts is dropped, and
+    // so is a 0-minute one, as it would expire before it fires
+    pub fn plan_end_notifications(
+        &self,
+        end_reminder_minutes: &[u32],
+        notify_end: bool,
+    ) -> Vec<PlannedNotification> {
+        let start = self.get_start();
+        let end = self.get_end();
+
+        let mut notifications = end_reminder_minutes
+            .iter()
+            .filter(|minutes_before| **minutes_before > 0)
+            .map(|minutes_before| {
+                PlannedNotification::new(
+                    NotificationKind::EndReminder {
+                        minutes_before: *minutes_before,
+                    },
+                    end - Duration::minutes((*minutes_before).into()),
+                    end - Duration::seconds(1),
+                )
+            })
+            .filter(|notification| start < notification.get_fire_at())
+            .collect::<Vec<_>>();
+        if notify_end {
+            notifications.push(PlannedNotification::new(
+                NotificationKind::End,
+                end,
+                end + Duration::minutes(END_NOTIFICATION_GRACE_MINUTES),
+            ));
+        }
+
+        notifications
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::day::Day;
+
+    #[test]
+    fn test_overnight_occurrence() {
+        let day = serde_json::from_str::<Day>(
+            r#"{ "day_of_week": "Friday", "tasks": [
+                { "title": "Night shift", "start_time": "23:30", "end_time": "01:00", "details": "" }
+            ] }"#,
+        )
+        .unwrap();
+        let friday = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let occurrence = Occurrence::new(&day.get_tasks()[0], friday);
+
+        assert_eq!(
+            occurrence.get_end(),
+            NaiveDate::from_ymd_opt(2026, 10, 17)
+                .unwrap()
+                .and_hms_opt(1, 0, 0)
+                .unwrap()
+        );
+        let start = occurrence.plan_notifications(&[])[0];
+        assert!(start.is_due(friday.and_hms_opt(23, 45, 0).unwrap()));
+        assert!(start.is_due(friday.succ_opt().unwrap().and_hms_opt(0, 30, 0).unwrap()));
+        assert!(!start.is_due(friday.and_hms_opt(0, 30, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_plan_notifications() {
+        let day = serde_json::from_str::<Day>(
+            r#"{ "day_of_week": "Monday", "tasks": [
+                { "title": "Standup", "start_time": "09:00", "end_time": "09:15", "details": "" }
+            ] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let at = |hour, minute| monday.and_hms_opt(hour, minute, 0).unwrap();
+
+        let notifications =
+            Occurrence::new(&day.get_tasks()[0], monday).plan_notifications(&[15, 5]);
+        let due_at = |date_time| {
+            notifications
+                .iter()
+                .filter(|notification| notification.is_due(date_time))
+                .map(|notification| notification.get_kind())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(due_at(at(8, 44)).is_empty());
+        assert_eq!(
+            due_at(at(8, 50)),
+            vec![NotificationKind::Reminder { minutes_before: 15 }]
+        );
+        assert_eq!(
+            due_at(at(8, 55)),
+            vec![
+                NotificationKind::Reminder { minutes_before: 15 },
+                NotificationKind::Reminder { minutes_before: 5 }
+            ]
+        );
+        assert_eq!(due_at(at(9, 0)), vec![NotificationKind::Start]);
+        assert!(due_at(at(9, 16)).is_empty());
+    }
+
+    #[test]
+    fn test_plan_end_notifications() {
+        let day = serde_json::from_str::<Day>(
+            r#"{ "day_of_week": "Monday", "tasks": [
+                { "title": "Exercise", "start_time": "19:00", "end_time": "19:35", "details": "" }
+            ] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let occurrence = Occurrence::new(&day.get_tasks()[0], monday);
+
+        let kinds = occurrence
+            .plan_end_notifications(&[5, 60], true)
+            .iter()
+            .map(|notification| (notification.get_kind(), notification.get_fire_at().time()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    NotificationKind::EndReminder { minutes_before: 5 },
+                    chrono::NaiveTime::from_hms_opt(19, 30, 0).unwrap()
+                ),
+                (
+                    NotificationKind::End,
+                    chrono::NaiveTime::from_hms_opt(19, 35, 0).unwrap()
+                ),
+            ]
+        );
+        assert!(occurrence.plan_end_notifications(&[], false).is_empty());
+        assert!(occurrence.plan_end_notifications(&[0], false).is_empty());
+    }
+}
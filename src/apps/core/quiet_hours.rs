@@ -0,0 +1,103 @@
+use super::task;
+
+use chrono::{Duration, NaiveDateTime, NaiveTime};
+use std::fmt;
+use std::str::FromStr;
+
+// a daily time range like 22:30-06:00, which may wrap past midnight
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuietHours {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn new(start: NaiveTime, end: NaiveTime) -> QuietHours {
+        QuietHours { start, end }
+    }
+
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            self.start <= time || time < self.end
+        }
+    }
+
+    // when the quiet hours containing `date_time` end
+    pub fn get_end_after(&self, date_time: NaiveDateTime) -> Option<NaiveDateTime> {
+        if !self.contains(date_time.time()) {
+            return None;
+        }
+
+        let end = date_time.date().and_time(self.end);
+        if end > date_time {
+            Some(end)
+        } else {
+            Some(end + Duration::days(1))
+        }
+    }
+}
+
+impl FromStr for QuietHours {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| format!("invalid quiet hours \"{}\" (expected HH:MM-HH:MM)", s))?;
+
+        match (task::parse_time(start), task::parse_time(end)) {
+            (Some(start), Some(end)) if start != end => Ok(QuietHours::new(start, end)),
+            _ => Err(format!(
+                "invalid quiet hours \"{}\" (expected HH:MM-HH:MM)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for QuietHours {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            task::format_time(&self.start),
+            task::format_time(&self.end)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_quiet_hours_past_midnight() {
+        let quiet_hours = "22:30-06:00".parse::<QuietHours>().unwrap();
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+
+        assert!(quiet_hours.contains(time(23, 0)));
+        assert!(quiet_hours.contains(time(5, 59)));
+        assert!(!quiet_hours.contains(time(6, 0)));
+        assert!(!quiet_hours.contains(time(22, 29)));
+        assert_eq!(
+            quiet_hours.get_end_after(monday.and_time(time(23, 0))),
+            Some(monday.succ_opt().unwrap().and_time(time(6, 0)))
+        );
+        assert_eq!(
+            quiet_hours.get_end_after(monday.and_time(time(1, 0))),
+            Some(monday.and_time(time(6, 0)))
+        );
+        assert_eq!(
+            quiet_hours.get_end_after(monday.and_time(time(12, 0))),
+            None
+        );
+        assert_eq!(quiet_hours.to_string(), "22:30-06:00");
+
+        assert!("22:30".parse::<QuietHours>().is_err());
+    }
+}
@@ -0,0 +1,537 @@
+use chrono::{Datelike, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+const WEEKDAY_CODES: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+// The supported subset of RFC 5545 RRULE: FREQ, INTERVAL, BYDAY (with an
+// ordinal for MONTHLY, e.g. "1MO" or "-1FR"), BYMONTHDAY and UNTIL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_month_day: Vec<i32>,
+    until: Option<NaiveDate>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct RecurrenceRecord {
+    #[serde(default)]
+    rule: Option<String>,
+    #[serde(default)]
+    start_date: Option<NaiveDate>,
+    #[serde(default)]
+    end_date: Option<NaiveDate>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Recurrence {
+    #[serde(
+        serialize_with = "serialize_rule",
+        skip_serializing_if = "Option::is_none"
+    )]
+    rule: Option<Rule>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_date: Option<NaiveDate>,
+}
+
+impl Rule {
+    pub fn get_frequency(&self) -> Frequency {
+        self.frequency
+    }
+
+    pub fn get_interval(&self) -> u32 {
+        self.interval
+    }
+
+    // BYDAY weekdays without an ordinal
+    pub fn get_weekdays(&self) -> Vec<Weekday> {
+        self.by_day
+            .iter()
+            .filter(|(ordinal, _)| ordinal.is_none())
+            .map(|(_, weekday)| *weekday)
+            .collect()
+    }
+
+    // the rule with its BYDAY weekdays moved by `days`, e.g. for a UTC start
+    // that falls on another day locally; ordinals and BYMONTHDAY can't be moved
+    pub fn shift_weekdays(&self, days: i64) -> Result<Rule, String> {
+        if days == 0 {
+            return Ok(self.clone());
+        }
+        if !self.by_month_day.is_empty() || self.by_day.iter().any(|(ordinal, _)| ordinal.is_some())
+        {
+            return Err(
+                "BYDAY ordinals and BYMONTHDAY can't be moved to the local day".to_string(),
+            );
+        }
+
+        let mut rule = self.clone();
+        for (_, weekday) in rule.by_day.iter_mut() {
+            let days_from_monday = (i64::from(weekday.num_days_from_monday()) + days).rem_euclid(7);
+            *weekday = Weekday::try_from(days_from_monday as u8).unwrap();
+        }
+        Ok(rule)
+    }
+
+    fn matches(&self, date: NaiveDate, anchor: Option<NaiveDate>, weekday: Weekday) -> bool {
+        let interval = i64::from(self.interval);
+
+        match self.frequency {
+            Frequency::Daily => {
+                let is_on_interval = match anchor {
+                    Some(anchor) => (date - anchor).num_days().rem_euclid(interval) == 0,
+                    None => true,
+                };
+
+                is_on_interval
+                    && (self.by_day.is_empty()
+                        || self
+                            .by_day
+                            .iter()
+                            .any(|(_, by_day)| *by_day == date.weekday()))
+            }
+            Frequency::Weekly => {
+                let is_on_interval = match anchor {
+                    Some(anchor) => {
+                        let weeks = (week_start(date) - week_start(anchor)).num_weeks();
+                        weeks.rem_euclid(interval) == 0
+                    }
+                    None => true,
+                };
+
+                is_on_interval
+                    && if self.by_day.is_empty() {
+                        date.weekday() == weekday
+                    } else {
+                        self.by_day
+                            .iter()
+                            .any(|(_, by_day)| *by_day == date.weekday())
+                    }
+            }
+            Frequency::Monthly => {
+                let is_on_interval = match anchor {
+                    Some(anchor) => {
+                        let months = (date.year() - anchor.year()) * 12 + date.month() as i32
+                            - anchor.month() as i32;
+                        i64::from(months).rem_euclid(interval) == 0
+                    }
+                    None => true,
+                };
+
+                is_on_interval
+                    && if !self.by_month_day.is_empty() {
+                        self.by_month_day
+                            .iter()
+                            .any(|month_day| matches_month_day(date, *month_day))
+                    } else if !self.by_day.is_empty() {
+                        self.by_day
+                            .iter()
+                            .any(|(ordinal, by_day)| matches_nth_weekday(date, *ordinal, *by_day))
+                    } else {
+                        // the day of the month of the start date, as in RFC 5545
+                        anchor.is_some_and(|anchor| date.day() == anchor.day())
+                    }
+            }
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let rule = rule.trim();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut by_day = Vec::new();
+        let mut by_month_day = Vec::new();
+        let mut until = None;
+
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("malformed part \"{}\"", part))?;
+
+            match key.to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return Err(format!("unsupported FREQ={}", value)),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(|| format!("invalid INTERVAL={}", value))?
+                }
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        by_day.push(parse_by_day(day)?);
+                    }
+                }
+                "BYMONTHDAY" => {
+                    for month_day in value.split(',') {
+                        by_month_day.push(
+                            month_day
+                                .parse::<i32>()
+                                .ok()
+                                .filter(|month_day| (1..=31).contains(&month_day.abs()))
+                                .ok_or_else(|| format!("invalid BYMONTHDAY={}", month_day))?,
+                        );
+                    }
+                }
+                "UNTIL" => {
+                    let date = value.get(..8).unwrap_or(value);
+                    until = Some(
+                        NaiveDate::parse_from_str(date, "%Y%m%d")
+                            .map_err(|_| format!("invalid UNTIL={}", value))?,
+                    )
+                }
+                _ => return Err(format!("unsupported {}", part)),
+            }
+        }
+
+        let frequency = frequency.ok_or_else(|| "missing FREQ".to_string())?;
+        if frequency != Frequency::Monthly
+            && (by_day.iter().any(|(ordinal, _)| ordinal.is_some()) || !by_month_day.is_empty())
+        {
+            return Err("BYDAY ordinals and BYMONTHDAY need FREQ=MONTHLY".to_string());
+        }
+
+        Ok(Rule {
+            frequency,
+            interval,
+            by_day,
+            by_month_day,
+            until,
+        })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let by_day = self
+                .by_day
+                .iter()
+                .map(|(ordinal, weekday)| {
+                    let code = WEEKDAY_CODES[weekday.num_days_from_monday() as usize];
+                    match ordinal {
+                        Some(ordinal) => format!("{}{}", ordinal, code),
+                        None => code.to_string(),
+                    }
+                })
+                .collect::<Vec<_>>();
+            write!(f, ";BYDAY={}", by_day.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let by_month_day = self
+                .by_month_day
+                .iter()
+                .map(|month_day| month_day.to_string())
+                .collect::<Vec<_>>();
+            write!(f, ";BYMONTHDAY={}", by_month_day.join(","))?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Recurrence {
+    pub fn from_record(record: RecurrenceRecord) -> Result<Recurrence, String> {
+        let rule = match record.rule {
+            Some(rule) => Some(
+                rule.parse::<Rule>()
+                    .map_err(|err| format!("invalid recurrence rule \"{}\": {}", rule, err))?,
+            ),
+            None => None,
+        };
+
+        if let (Some(start_date), Some(end_date)) = (record.start_date, record.end_date) {
+            if end_date < start_date {
+                return Err(format!(
+                    "recurrence end_date {} is before start_date {}",
+                    end_date, start_date
+                ));
+            }
+        }
+        if let Some(rule) = &rule {
+            if rule.interval > 1 && record.start_date.is_none() {
+                return Err(format!(
+                    "recurrence rule \"{}\" needs a start_date to count intervals from",
+                    rule
+                ));
+            }
+            if rule.frequency == Frequency::Monthly
+                && rule.by_day.is_empty()
+                && rule.by_month_day.is_empty()
+                && record.start_date.is_none()
+            {
+                return Err(format!(
+                    "recurrence rule \"{}\" needs BYDAY, BYMONTHDAY or a start_date",
+                    rule
+                ));
+            }
+        }
+
+        Ok(Recurrence {
+            rule,
+            start_date: record.start_date,
+            end_date: record.end_date,
+        })
+    }
+
+    pub fn get_start_date(&self) -> Option<NaiveDate> {
+        self.start_date
+    }
+
+    fn get_until(&self) -> Option<NaiveDate> {
+        self.rule
+            .as_ref()
+            .and_then(|rule| rule.until)
+            .into_iter()
+            .chain(self.end_date)
+            .min()
+    }
+
+    // `weekday` is the day the task is listed under in the weekly template
+    pub fn occurs_on(&self, date: NaiveDate, weekday: Weekday) -> bool {
+        let until = self.get_until();
+
+        if self.start_date.is_some_and(|start_date| date < start_date)
+            || until.is_some_and(|until| date > until)
+        {
+            return false;
+        }
+
+        match &self.rule {
+            Some(rule) => rule.matches(date, self.start_date, weekday),
+            None => date.weekday() == weekday,
+        }
+    }
+}
+
+// the RFC 5545 RRULE value for a task listed under `weekday`; the days the
+// template implies are spelled out, and the end date is folded into UNTIL
+pub fn to_ical_rule(recurrence: Option<&Recurrence>, weekday: Weekday) -> String {
+    let mut rule = recurrence
+        .and_then(|recurrence| recurrence.rule.clone())
+        .unwrap_or(Rule {
+            frequency: Frequency::Weekly,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            until: None,
+        });
+    if rule.by_day.is_empty() && rule.by_month_day.is_empty() {
+        match rule.frequency {
+            Frequency::Daily => (),
+            Frequency::Weekly => rule.by_day.push((None, weekday)),
+            Frequency::Monthly => rule.by_month_day.extend(
+                recurrence
+                    .and_then(|recurrence| recurrence.start_date)
+                    .map(|start_date| start_date.day() as i32),
+            ),
+        }
+    }
+    rule.until = None;
+
+    // UNTIL has to be a date-time like the DTSTART it goes with
+    match recurrence.and_then(|recurrence| recurrence.get_until()) {
+        Some(until) => format!("{};UNTIL={}T235959", rule, until.format("%Y%m%d")),
+        None => rule.to_string(),
+    }
+}
+
+fn parse_by_day(day: &str) -> Result<(Option<i32>, Weekday), String> {
+    let day = day.trim().to_uppercase();
+    // weekday codes are ASCII, and splitting elsewhere could cut a character
+    if !day.is_ascii() {
+        return Err(format!("invalid BYDAY={}", day));
+    }
+    let split_at = day.len().saturating_sub(2);
+    let (ordinal, code) = day.split_at(split_at);
+
+    let weekday = WEEKDAY_CODES
+        .iter()
+        .position(|weekday_code| *weekday_code == code)
+        .and_then(|idx| Weekday::try_from(idx as u8).ok())
+        .ok_or_else(|| format!("invalid BYDAY={}", day))?;
+    let ordinal = match ordinal {
+        "" => None,
+        ordinal => Some(
+            ordinal
+                .parse::<i32>()
+                .ok()
+                .filter(|ordinal| (1..=5).contains(&ordinal.abs()))
+                .ok_or_else(|| format!("invalid BYDAY={}", day))?,
+        ),
+    };
+
+    Ok((ordinal, weekday))
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - chrono::Duration::days(date.weekday().num_days_from_monday().into())
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let first = date.with_day(1).expect("every month has a first day");
+    let next_first = first + Months::new(1);
+
+    (next_first - first).num_days() as u32
+}
+
+fn matches_month_day(date: NaiveDate, month_day: i32) -> bool {
+    if month_day > 0 {
+        date.day() as i32 == month_day
+    } else {
+        days_in_month(date) as i32 + month_day + 1 == date.day() as i32
+    }
+}
+
+fn matches_nth_weekday(date: NaiveDate, ordinal: Option<i32>, weekday: Weekday) -> bool {
+    if date.weekday() != weekday {
+        return false;
+    }
+
+    match ordinal {
+        None => true,
+        Some(ordinal) if ordinal > 0 => ((date.day() - 1) / 7 + 1) as i32 == ordinal,
+        Some(ordinal) => ((days_in_month(date) - date.day()) / 7 + 1) as i32 == -ordinal,
+    }
+}
+
+fn serialize_rule<S: Serializer>(rule: &Option<Rule>, serializer: S) -> Result<S::Ok, S::Error> {
+    match rule {
+        Some(rule) => serializer.serialize_str(&rule.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recurrence(json: &str) -> Recurrence {
+        Recurrence::from_record(serde_json::from_str(json).unwrap()).unwrap()
+    }
+
+    fn matching_dates(recurrence: &Recurrence, weekday: Weekday) -> Vec<String> {
+        NaiveDate::from_ymd_opt(2026, 11, 1)
+            .unwrap()
+            .iter_days()
+            .take(30)
+            .filter(|date| recurrence.occurs_on(*date, weekday))
+            .map(|date| date.format("%m/%d").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_every_other_week() {
+        let recurrence =
+            recurrence(r#"{ "rule": "FREQ=WEEKLY;INTERVAL=2", "start_date": "2026-11-02" }"#);
+
+        assert_eq!(
+            matching_dates(&recurrence, Weekday::Mon),
+            vec!["11/02", "11/16", "11/30"]
+        );
+    }
+
+    #[test]
+    fn test_first_and_last_weekday_of_month() {
+        let first_monday = recurrence(r#"{ "rule": "FREQ=MONTHLY;BYDAY=1MO" }"#);
+        let last_friday = recurrence(r#"{ "rule": "FREQ=MONTHLY;BYDAY=-1FR" }"#);
+
+        assert_eq!(matching_dates(&first_monday, Weekday::Mon), vec!["11/02"]);
+        assert_eq!(matching_dates(&last_friday, Weekday::Fri), vec!["11/27"]);
+    }
+
+    #[test]
+    fn test_monthly_on_start_day() {
+        let recurrence = recurrence(r#"{ "rule": "FREQ=MONTHLY", "start_date": "2026-10-09" }"#);
+
+        assert_eq!(matching_dates(&recurrence, Weekday::Fri), vec!["11/09"]);
+        assert_eq!(
+            to_ical_rule(Some(&recurrence), Weekday::Fri),
+            "FREQ=MONTHLY;BYMONTHDAY=9"
+        );
+        assert_eq!(
+            Recurrence::from_record(serde_json::from_str(r#"{ "rule": "FREQ=MONTHLY" }"#).unwrap())
+                .unwrap_err(),
+            "recurrence rule \"FREQ=MONTHLY\" needs BYDAY, BYMONTHDAY or a start_date"
+        );
+    }
+
+    #[test]
+    fn test_weekdays_within_range() {
+        let recurrence = recurrence(
+            r#"{ "rule": "FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR", "start_date": "2026-11-04", "end_date": "2026-11-10" }"#,
+        );
+
+        assert_eq!(
+            matching_dates(&recurrence, Weekday::Mon),
+            vec!["11/04", "11/05", "11/06", "11/09", "11/10"]
+        );
+    }
+
+    #[test]
+    fn test_rule_round_trip() {
+        let rule = "FREQ=MONTHLY;INTERVAL=2;BYDAY=1MO,-1FR;UNTIL=20271231"
+            .parse::<Rule>()
+            .unwrap();
+        assert_eq!(
+            rule.to_string(),
+            "FREQ=MONTHLY;INTERVAL=2;BYDAY=1MO,-1FR;UNTIL=20271231"
+        );
+
+        assert_eq!(
+            "FREQ=YEARLY".parse::<Rule>().unwrap_err(),
+            "unsupported FREQ=YEARLY"
+        );
+        assert_eq!(
+            "FREQ=WEEKLY;COUNT=3".parse::<Rule>().unwrap_err(),
+            "unsupported COUNT=3"
+        );
+        assert_eq!(
+            "FREQ=WEEKLY;BYDAY=月".parse::<Rule>().unwrap_err(),
+            "invalid BYDAY=月"
+        );
+        assert!("FREQ=MONTHLY;BYDAY=1月曜".parse::<Rule>().is_err());
+        assert!(Recurrence::from_record(
+            serde_json::from_str(r#"{ "rule": "FREQ=WEEKLY;INTERVAL=2" }"#).unwrap()
+        )
+        .is_err());
+    }
+}
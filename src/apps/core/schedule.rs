@@ -1,20 +1,358 @@
+use super::date_override::{DateOverride, DateOverrideRecord};
 use super::day;
+use super::day_of_week::{DayOfWeek, Language};
+use super::notification::PlannedNotification;
+use super::occurrence::{Occurrence, END_NOTIFICATION_GRACE_MINUTES};
+use super::task::{self, Task};
+use super::validation::{Issue, Severity, ValidationReport};
 
-use serde::Deserialize;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Deserialize, Clone, Debug)]
+pub struct ScheduleRecord {
+    days: Vec<day::DayRecord>,
+    #[serde(default)]
+    overrides: BTreeMap<String, DateOverrideRecord>,
+    #[serde(default)]
+    reminders: Vec<u32>,
+    #[serde(default)]
+    end_reminders: Vec<u32>,
+    #[serde(default)]
+    notify_end: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(try_from = "ScheduleRecord")]
 pub struct Schedule {
     days: Vec<day::Day>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    overrides: BTreeMap<NaiveDate, DateOverride>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    reminders: Vec<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    end_reminders: Vec<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    notify_end: bool,
+}
+
+impl TryFrom<ScheduleRecord> for Schedule {
+    type Error = String;
+
+    fn try_from(record: ScheduleRecord) -> Result<Self, Self::Error> {
+        Schedule::from_record(record).map_err(|report| {
+            report
+                .get_issues()
+                .iter()
+                .map(|issue| issue.get_description())
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+    }
 }
 
 impl Schedule {
     pub fn new() -> Schedule {
-        Schedule { days: Vec::new() }
+        Schedule {
+            days: Vec::new(),
+            overrides: BTreeMap::new(),
+            reminders: Vec::new(),
+            end_reminders: Vec::new(),
+            notify_end: false,
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Schedule, ValidationReport> {
+        match serde_json::from_str::<ScheduleRecord>(contents) {
+            Ok(record) => Schedule::from_record(record),
+            Err(err) => {
+                let mut report = ValidationReport::new();
+                report.push(Issue::new(Severity::Error, "", &err.to_string()));
+                Err(report)
+            }
+        }
+    }
+
+    pub fn from_record(record: ScheduleRecord) -> Result<Schedule, ValidationReport> {
+        let mut report = ValidationReport::new();
+
+        let mut days = Vec::new();
+        for day in record.days {
+            match day::Day::from_record(day) {
+                Ok(day) => days.push(day),
+                Err(issues) => report.extend(issues),
+            }
+        }
+
+        let mut overrides = BTreeMap::new();
+        for (date, date_override) in record.overrides {
+            let Ok(parsed_date) = NaiveDate::parse_from_str(&date, "%Y-%m-%d") else {
+                report.push(Issue::new(
+                    Severity::Error,
+                    "overrides",
+                    &format!("invalid date \"{}\" (expected YYYY-MM-DD)", date),
+                ));
+                continue;
+            };
+
+            match DateOverride::from_record(date_override, parsed_date) {
+                Ok(date_override) => {
+                    overrides.insert(parsed_date, date_override);
+                }
+                Err(issues) => report.extend(issues),
+            }
+        }
+
+        if report.has_errors() {
+            return Err(report);
+        }
+
+        let schedule = Schedule {
+            days,
+            overrides,
+            reminders: record.reminders,
+            end_reminders: record.end_reminders,
+            notify_end: record.notify_end,
+        };
+        let report = schedule.validate();
+        if report.has_errors() {
+            return Err(report);
+        }
+
+        Ok(schedule)
+    }
+
+    // adds the days and overrides of `other`, whose overrides win for dates
+    // both have; the schedule-wide notification settings stay this one's
+    pub fn merge(&mut self, other: Schedule) {
+        self.days.extend(other.days);
+        self.overrides.extend(other.overrides);
     }
 
     pub fn get_days(&self) -> &Vec<day::Day> {
         self.days.as_ref()
     }
+
+    pub fn get_overrides(&self) -> &BTreeMap<NaiveDate, DateOverride> {
+        &self.overrides
+    }
+
+    pub fn get_day_of_week(&self, weekday: Weekday) -> DayOfWeek {
+        let language = self
+            .days
+            .first()
+            .map(|day| day.get_day_of_week().get_language())
+            .unwrap_or_default();
+
+        DayOfWeek::new(weekday, language)
+    }
+
+    // the template tasks recurring on the date with that date's override applied
+    pub fn get_occurrences_on(&self, date: NaiveDate) -> Vec<Occurrence<'_>> {
+        let date_override = self.overrides.get(&date);
+
+        let mut occurrences = self
+            .days
+            .iter()
+            .flat_map(|day| day.get_tasks())
+            .filter(|task| task.occurs_on(date))
+            .filter(|task| {
+                !date_override.is_some_and(|date_override| date_override.is_removed(task))
+            })
+            .map(|task| Occurrence::new(task, date))
+            .collect::<Vec<_>>();
+
+        if let Some(date_override) = date_override {
+            occurrences.extend(
+                date_override
+                    .get_added_tasks()
+                    .iter()
+                    .map(|task| Occurrence::new(task, date)),
+            );
+        }
+        occurrences.sort_by_key(|occurrence| occurrence.get_start());
+
+        occurrences
+    }
+
+    // occurrences that started on an earlier date are included as long as
+    // they can still be running (or have just ended) at `date_time`, and later
+    // ones as long as a reminder for them can already be due
+    pub fn get_occurrences_around(&self, date_time: NaiveDateTime) -> Vec<Occurrence<'_>> {
+        self.get_occurrences_between(date_time, date_time)
+    }
+
+    // the earliest notification firing after `after` and no later than `until`
+    pub fn get_next_notification_time(
+        &self,
+        after: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> Option<NaiveDateTime> {
+        self.get_occurrences_between(after, until)
+            .iter()
+            .flat_map(|occurrence| self.plan_notifications(occurrence))
+            .map(|notification| notification.get_fire_at())
+            .filter(|fire_at| after < *fire_at && *fire_at <= until)
+            .min()
+    }
+
+    fn get_occurrences_between(
+        &self,
+        first_date_time: NaiveDateTime,
+        last_date_time: NaiveDateTime,
+    ) -> Vec<Occurrence<'_>> {
+        let first_date = (first_date_time - Duration::minutes(END_NOTIFICATION_GRACE_MINUTES))
+            .date()
+            - Duration::days(self.get_max_end_day_offset().into());
+        let last_date =
+            (last_date_time + Duration::minutes(self.get_max_reminder_minutes().into())).date();
+
+        first_date
+            .iter_days()
+            .take_while(|date| *date <= last_date)
+            .flat_map(|date| self.get_occurrences_on(date))
+            .collect()
+    }
+
+    // occurrences starting after `after` and no later than `until`, in order
+    pub fn get_upcoming_occurrences(
+        &self,
+        after: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> Vec<Occurrence<'_>> {
+        after
+            .date()
+            .iter_days()
+            .take_while(|date| *date <= until.date())
+            .flat_map(|date| self.get_occurrences_on(date))
+            .filter(|occurrence| after < occurrence.get_start() && occurrence.get_start() <= until)
+            .collect()
+    }
+
+    // the first occurrence starting once `occurrence` has ended, on the date it ends
+    pub fn get_next_occurrence(&self, occurrence: &Occurrence) -> Option<Occurrence<'_>> {
+        let end = occurrence.get_end();
+
+        self.get_occurrences_on(end.date())
+            .into_iter()
+            .find(|next| next.get_start() >= end)
+    }
+
+    // per-task settings take precedence over the schedule-wide ones
+    pub fn plan_notifications(&self, occurrence: &Occurrence) -> Vec<PlannedNotification> {
+        let task = occurrence.get_task();
+        let mut notifications = occurrence.plan_notifications(self.get_reminder_minutes(task));
+        notifications.extend(occurrence.plan_end_notifications(
+            task.get_end_reminders().unwrap_or(&self.end_reminders),
+            task.get_notify_end().unwrap_or(self.notify_end),
+        ));
+
+        notifications
+    }
+
+    fn get_reminder_minutes<'a>(&'a self, task: &'a Task) -> &'a [u32] {
+        task.get_reminders().unwrap_or(&self.reminders)
+    }
+
+    // occurrences from earlier dates that are still running when `date` begins
+    pub fn get_continuing_occurrences(&self, date: NaiveDate) -> Vec<Occurrence<'_>> {
+        let start_of_date = date.and_time(chrono::NaiveTime::MIN);
+
+        (1..=i64::from(self.get_max_end_day_offset()))
+            .rev()
+            .flat_map(|days_ago| self.get_occurrences_on(date - Duration::days(days_ago)))
+            .filter(|occurrence| occurrence.get_end() > start_of_date)
+            .collect()
+    }
+
+    // weekly tasks and the tasks date overrides add
+    pub fn get_all_tasks(&self) -> impl Iterator<Item = &Task> {
+        self.days.iter().flat_map(|day| day.get_tasks()).chain(
+            self.overrides
+                .values()
+                .flat_map(|date_override| date_override.get_added_tasks()),
+        )
+    }
+
+    fn get_max_end_day_offset(&self) -> u32 {
+        self.get_all_tasks()
+            .map(|task| task.get_end_day_offset())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn get_max_reminder_minutes(&self) -> u32 {
+        self.get_all_tasks()
+            .flat_map(|task| self.get_reminder_minutes(task))
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn set_display_language(&mut self, language: Language) {
+        for day in self.days.iter_mut() {
+            day.set_display_language(language);
+        }
+    }
+
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::new();
+        let mut seen_days = HashMap::new();
+        let mut seen_task_ids = HashMap::new();
+
+        if self.end_reminders.contains(&0) {
+            report.push(Issue::new(
+                Severity::Warning,
+                "end_reminders",
+                task::ZERO_END_REMINDER,
+            ));
+        }
+
+        for (day_idx, day) in self.days.iter().enumerate() {
+            let weekday = day.get_day_of_week().get_weekday();
+            if let Some(first_idx) = seen_days.insert(weekday, day_idx) {
+                report.push(Issue::new(
+                    Severity::Warning,
+                    &day.get_day_of_week().to_string(),
+                    &format!("duplicate day (also defined as day #{})", first_idx),
+                ));
+            }
+
+            for (task_idx, task) in day.get_tasks().iter().enumerate() {
+                let Some(task_id) = task.get_explicit_id() else {
+                    continue;
+                };
+
+                let location = format!("{}: task #{}", day.get_day_of_week(), task_idx);
+                if let Some(first_location) = seen_task_ids.insert(task_id, location.clone()) {
+                    report.push(Issue::new(
+                        Severity::Error,
+                        &location,
+                        &format!(
+                            "duplicate id \"{}\" (first used by {})",
+                            task_id, first_location
+                        ),
+                    ));
+                }
+            }
+
+            report.extend(day.validate());
+        }
+
+        for (date, date_override) in self.overrides.iter() {
+            let weekly_tasks = self
+                .days
+                .iter()
+                .flat_map(|day| day.get_tasks())
+                .filter(|task| task.occurs_on(*date))
+                .collect::<Vec<_>>();
+
+            report.extend(date_override.validate(*date, &weekly_tasks));
+        }
+
+        report
+    }
 }
 
 #[cfg(test)]
@@ -27,4 +365,221 @@ mod tests {
 
         assert_eq!(schedule.get_days().len(), 0);
     }
+
+    #[test]
+    fn test_parse_reports_every_issue() {
+        let report = Schedule::parse(
+            r#"{ "days": [
+                { "day_of_week": "Monday", "tasks": [
+                    { "title": "a", "start_time": "9:00", "end_time": "25:00", "details": "" },
+                    { "title": "b", "start_time": "noon", "end_time": "13:00", "details": "" }
+                ] },
+                { "day_of_week": "Someday", "tasks": [] }
+            ] }"#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            report.to_string(),
+            [
+                "error: Monday: task #0: invalid end_time \"25:00\"",
+                "error: Monday: task #1: invalid start_time \"noon\"",
+                "error: unknown day of week \"Someday\"",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_validate_schedule() {
+        let schedule = Schedule::parse(
+            r#"{ "days": [
+                { "day_of_week": "Monday", "tasks": [
+                    { "id": "gym", "title": "Gym", "start_time": "19:00", "end_time": "20:00", "details": "" }
+                ] },
+                { "day_of_week": "月曜日", "tasks": [] }
+            ] }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            schedule.validate().to_string(),
+            "warning: 月曜日: duplicate day (also defined as day #0)"
+        );
+
+        let report = Schedule::parse(
+            r#"{ "days": [
+                { "day_of_week": "Monday", "tasks": [
+                    { "id": "gym", "title": "Gym", "start_time": "19:00", "end_time": "20:00", "details": "" }
+                ] },
+                { "day_of_week": "Tuesday", "tasks": [
+                    { "id": "gym", "title": "Gym", "start_time": "19:00", "end_time": "20:00", "details": "" }
+                ] }
+            ] }"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            report.to_string(),
+            "error: Tuesday: task #0: duplicate id \"gym\" (first used by Monday: task #0)"
+        );
+
+        let schedule = Schedule::parse(
+            r#"{
+                "end_reminders": [0, 5],
+                "days": [],
+                "overrides": {
+                    "2026-11-03": {
+                        "add": [{ "title": "Trip", "start_time": "08:00", "end_time": "22:00", "end_reminders": [0], "details": "" }]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            schedule.validate().to_string(),
+            "warning: end_reminders: 0-minute end reminder never fires; use notify_end\n\
+             warning: 2026-11-03: add #0: 0-minute end reminder never fires; use notify_end"
+        );
+    }
+
+    #[test]
+    fn test_overnight_occurrences() {
+        let schedule = Schedule::parse(
+            r#"{ "days": [
+                { "day_of_week": "Sunday", "tasks": [
+                    { "title": "Night shift", "start_time": "23:30", "end_time": "01:00", "details": "" }
+                ] },
+                { "day_of_week": "Monday", "tasks": [
+                    { "title": "Breakfast", "start_time": "07:00", "end_time": "07:30", "details": "" }
+                ] }
+            ] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+
+        let titles = schedule
+            .get_occurrences_around(monday.and_hms_opt(0, 30, 0).unwrap())
+            .iter()
+            .filter(|occurrence| {
+                occurrence.plan_notifications(&[])[0].is_due(monday.and_hms_opt(0, 30, 0).unwrap())
+            })
+            .map(|occurrence| occurrence.get_task().get_title())
+            .collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Night shift"]);
+
+        let continuing_occurrences = schedule.get_continuing_occurrences(monday);
+        assert_eq!(continuing_occurrences.len(), 1);
+        assert_eq!(
+            continuing_occurrences[0].get_task().get_title(),
+            "Night shift"
+        );
+        assert!(schedule
+            .get_continuing_occurrences(monday.succ_opt().unwrap())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_date_overrides() {
+        let schedule = Schedule::parse(
+            r#"{
+                "days": [
+                    { "day_of_week": "Tuesday", "tasks": [
+                        { "title": "Gym", "start_time": "19:00", "end_time": "20:00", "details": "" },
+                        { "title": "Dinner", "start_time": "18:00", "end_time": "18:30", "details": "" }
+                    ] }
+                ],
+                "overrides": {
+                    "2026-11-03": { "cancel": ["Gym"] },
+                    "2026-11-10": {
+                        "replace_all": true,
+                        "add": [{ "title": "Trip", "start_time": "08:00", "end_time": "22:00", "details": "" }]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let titles_on = |date: &str| {
+            schedule
+                .get_occurrences_on(NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap())
+                .iter()
+                .map(|occurrence| occurrence.get_task().get_title().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(titles_on("2026-10-27"), vec!["Dinner", "Gym"]);
+        assert_eq!(titles_on("2026-11-03"), vec!["Dinner"]);
+        assert_eq!(titles_on("2026-11-10"), vec!["Trip"]);
+
+        let report = Schedule::parse(
+            r#"{ "days": [], "overrides": { "11/03": {}, "2026-11-04": { "cancel": ["Gym"] } } }"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            report.to_string(),
+            "error: overrides: invalid date \"11/03\" (expected YYYY-MM-DD)"
+        );
+    }
+
+    #[test]
+    fn test_recurring_occurrences() {
+        let schedule = Schedule::parse(
+            r#"{ "days": [
+                { "day_of_week": "Monday", "tasks": [
+                    { "title": "Standup", "start_time": "09:00", "end_time": "09:15", "details": "",
+                      "recurrence": { "rule": "FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR" } },
+                    { "title": "Review", "start_time": "10:00", "end_time": "11:00", "details": "",
+                      "recurrence": { "rule": "FREQ=MONTHLY;BYDAY=1MO" } }
+                ] }
+            ] }"#,
+        )
+        .unwrap();
+        let titles_on = |date: &str| {
+            schedule
+                .get_occurrences_on(NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap())
+                .iter()
+                .map(|occurrence| occurrence.get_task().get_title().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(titles_on("2026-11-02"), vec!["Standup", "Review"]);
+        assert_eq!(titles_on("2026-11-05"), vec!["Standup"]);
+        assert_eq!(titles_on("2026-11-09"), vec!["Standup"]);
+        assert!(titles_on("2026-11-07").is_empty());
+    }
+
+    #[test]
+    fn test_get_next_notification_time() {
+        let schedule = Schedule::parse(
+            r#"{ "reminders": [10], "days": [
+                { "day_of_week": "Monday", "tasks": [
+                    { "title": "Standup", "start_time": "09:00", "end_time": "09:15", "details": "" }
+                ] },
+                { "day_of_week": "Tuesday", "tasks": [
+                    { "title": "Early run", "start_time": "00:05", "end_time": "01:00", "reminders": [], "details": "" }
+                ] }
+            ] }"#,
+        )
+        .unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let at =
+            |date: NaiveDate, hour, minute, second| date.and_hms_opt(hour, minute, second).unwrap();
+
+        assert_eq!(
+            schedule.get_next_notification_time(at(monday, 8, 0, 0), at(monday, 9, 0, 0)),
+            Some(at(monday, 8, 50, 0))
+        );
+        assert_eq!(
+            schedule.get_next_notification_time(at(monday, 8, 50, 0), at(monday, 9, 30, 0)),
+            Some(at(monday, 9, 0, 0))
+        );
+        assert_eq!(
+            schedule.get_next_notification_time(at(monday, 9, 0, 0), at(monday, 9, 30, 0)),
+            None
+        );
+
+        let tuesday = monday.succ_opt().unwrap();
+        assert_eq!(
+            schedule.get_next_notification_time(at(monday, 23, 59, 30), at(tuesday, 0, 10, 0)),
+            Some(at(tuesday, 0, 5, 0))
+        );
+    }
 }
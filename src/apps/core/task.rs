@@ -1,48 +1,243 @@
-use chrono::NaiveTime;
-use serde::Deserialize;
+use super::recurrence::{Recurrence, RecurrenceRecord};
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Timelike, Weekday};
+use serde::{Deserialize, Serialize, Serializer};
+
+const TIME_FORMATS: [&str; 4] = ["%H:%M:%S", "%H:%M", "%I:%M:%S %p", "%I:%M %p"];
+pub const ZERO_END_REMINDER: &str = "0-minute end reminder never fires; use notify_end";
 
 #[derive(Deserialize, Clone, Debug)]
-pub struct Task {
+pub struct TaskRecord {
+    #[serde(default)]
+    id: Option<String>,
     title: String,
     start_time: String,
     end_time: String,
+    #[serde(default)]
+    end_day_offset: Option<u32>,
+    #[serde(default)]
+    recurrence: Option<RecurrenceRecord>,
+    #[serde(default)]
+    reminders: Option<Vec<u32>>,
+    #[serde(default)]
+    end_reminders: Option<Vec<u32>>,
+    #[serde(default)]
+    notify_end: Option<bool>,
+    #[serde(default)]
+    silent: bool,
     details: String,
 }
 
+#[derive(Serialize, Clone, Debug)]
+pub struct Task {
+    #[serde(skip)]
+    id: String,
+    #[serde(rename = "id", skip_serializing_if = "Option::is_none")]
+    explicit_id: Option<String>,
+    title: String,
+    #[serde(serialize_with = "serialize_time")]
+    start_time: NaiveTime,
+    #[serde(serialize_with = "serialize_time")]
+    end_time: NaiveTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_day_offset: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recurrence: Option<Recurrence>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reminders: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_reminders: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    notify_end: Option<bool>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    silent: bool,
+    details: String,
+    #[serde(skip)]
+    weekday: Weekday,
+}
+
 impl Task {
+    pub fn from_record(record: TaskRecord, weekday: Weekday) -> Result<Task, Vec<String>> {
+        let start_time = parse_time(&record.start_time);
+        let end_time = parse_time(&record.end_time);
+        let recurrence = record.recurrence.map(Recurrence::from_record).transpose();
+
+        let (start_time, end_time, recurrence) = match (start_time, end_time, recurrence) {
+            (Some(start_time), Some(end_time), Ok(recurrence)) => {
+                (start_time, end_time, recurrence)
+            }
+            (start_time, end_time, recurrence) => {
+                let mut errors = Vec::new();
+                if start_time.is_none() {
+                    errors.push(format!("invalid start_time \"{}\"", record.start_time));
+                }
+                if end_time.is_none() {
+                    errors.push(format!("invalid end_time \"{}\"", record.end_time));
+                }
+                if let Err(err) = recurrence {
+                    errors.push(err);
+                }
+                return Err(errors);
+            }
+        };
+        let id = match &record.id {
+            Some(id) => id.clone(),
+            None => content_hash(weekday, &record.title, &start_time),
+        };
+
+        Ok(Task {
+            id,
+            explicit_id: record.id,
+            title: record.title,
+            start_time,
+            end_time,
+            end_day_offset: record.end_day_offset,
+            recurrence,
+            reminders: record.reminders,
+            end_reminders: record.end_reminders,
+            notify_end: record.notify_end,
+            silent: record.silent,
+            details: record.details,
+            weekday,
+        })
+    }
+
+    pub fn get_id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn get_explicit_id(&self) -> Option<&str> {
+        self.explicit_id.as_deref()
+    }
+
     pub fn get_title(&self) -> &str {
         self.title.as_str()
     }
 
-    pub fn get_start_time(&self) -> &str {
-        self.start_time.as_str()
+    pub fn get_start_time(&self) -> NaiveTime {
+        self.start_time
     }
 
-    pub fn get_end_time(&self) -> &str {
-        self.end_time.as_str()
+    pub fn get_end_time(&self) -> NaiveTime {
+        self.end_time
     }
 
     pub fn get_details(&self) -> &str {
         self.details.as_str()
     }
 
-    pub fn get_time_range(&self) -> (bool, NaiveTime, NaiveTime) {
-        let (is_converted_start, start_time) = Task::convert_string_to_time(self.get_start_time());
-        let (is_converted_end, end_time) = Task::convert_string_to_time(self.get_end_time());
+    // how many days after the start date the task ends; an end_time earlier
+    // than start_time without an explicit offset means it ends the next day
+    pub fn get_end_day_offset(&self) -> u32 {
+        match self.end_day_offset {
+            Some(end_day_offset) => end_day_offset,
+            None if self.end_time < self.start_time => 1,
+            None => 0,
+        }
+    }
+
+    pub fn get_explicit_end_day_offset(&self) -> Option<u32> {
+        self.end_day_offset
+    }
+
+    // minutes before the start; `None` falls back to the schedule-wide setting
+    pub fn get_reminders(&self) -> Option<&Vec<u32>> {
+        self.reminders.as_ref()
+    }
+
+    // minutes before the end; `None` falls back to the schedule-wide setting
+    pub fn get_end_reminders(&self) -> Option<&Vec<u32>> {
+        self.end_reminders.as_ref()
+    }
+
+    pub fn get_notify_end(&self) -> Option<bool> {
+        self.notify_end
+    }
+
+    // notifications for silent tasks are shown without sound
+    pub fn is_silent(&self) -> bool {
+        self.silent
+    }
 
-        (is_converted_start && is_converted_end, start_time, end_time)
+    pub fn get_recurrence(&self) -> Option<&Recurrence> {
+        self.recurrence.as_ref()
     }
 
-    pub fn get_memory_address(&self) -> usize {
-        self as *const _ as usize
+    // the day the task is listed under
+    pub fn get_weekday(&self) -> Weekday {
+        self.weekday
     }
 
-    fn convert_string_to_time(time_str: &str) -> (bool, NaiveTime) {
-        match NaiveTime::parse_from_str(time_str, "%H:%M:%S") {
-            Ok(time_result) => (true, time_result),
-            Err(_) => (false, NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
+    // whether an occurrence of the task starts on `date`
+    pub fn occurs_on(&self, date: NaiveDate) -> bool {
+        match &self.recurrence {
+            Some(recurrence) => recurrence.occurs_on(date, self.weekday),
+            None => date.weekday() == self.weekday,
         }
     }
+
+    pub fn get_duration(&self) -> Duration {
+        Duration::days(self.get_end_day_offset().into()) + (self.end_time - self.start_time)
+    }
+}
+
+pub fn parse_time(time_str: &str) -> Option<NaiveTime> {
+    let normalized = normalize_meridiem(time_str.trim());
+
+    TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(&normalized, format).ok())
+}
+
+pub fn format_time(time: &NaiveTime) -> String {
+    if time.second() == 0 {
+        time.format("%H:%M").to_string()
+    } else {
+        time.format("%H:%M:%S").to_string()
+    }
+}
+
+pub fn format_end_time(task: &Task) -> String {
+    match task.get_end_day_offset() {
+        0 => format_time(&task.get_end_time()),
+        end_day_offset => format!(
+            "{} (+{})",
+            format_time(&task.get_end_time()),
+            end_day_offset
+        ),
+    }
+}
+
+// FNV-1a, so that derived ids stay the same across builds and platforms
+fn content_hash(weekday: Weekday, title: &str, start_time: &NaiveTime) -> String {
+    let key = format!(
+        "{}\u{1f}{}\u{1f}{}",
+        weekday.num_days_from_monday(),
+        title,
+        start_time.format("%H:%M:%S")
+    );
+    let hash = key.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+
+    format!("{:016x}", hash)
+}
+
+fn serialize_time<S: Serializer>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_time(time))
+}
+
+// "9:30pm" -> "9:30 pm"
+fn normalize_meridiem(time_str: &str) -> String {
+    let lower = time_str.to_lowercase();
+
+    match lower
+        .strip_suffix("am")
+        .or_else(|| lower.strip_suffix("pm"))
+    {
+        Some(time_part) => format!("{} {}", time_part.trim_end(), &lower[lower.len() - 2..]),
+        None => lower,
+    }
 }
 
 #[cfg(test)]
@@ -51,16 +246,136 @@ mod tests {
 
     #[test]
     fn test_task() {
-        let task = Task {
-            title: "Test Task".to_string(),
-            start_time: "00:00:00".to_string(),
-            end_time: "01:00:00".to_string(),
-            details: "This is a test task".to_string(),
-        };
+        let task = Task::from_record(
+            TaskRecord {
+                id: None,
+                title: "Test Task".to_string(),
+                start_time: "00:00:00".to_string(),
+                end_time: "01:00".to_string(),
+                end_day_offset: None,
+                recurrence: None,
+                reminders: None,
+                end_reminders: None,
+                notify_end: None,
+                silent: false,
+                details: "This is a test task".to_string(),
+            },
+            Weekday::Mon,
+        )
+        .unwrap();
 
         assert_eq!(task.get_title(), "Test Task");
-        assert_eq!(task.get_start_time(), "00:00:00");
-        assert_eq!(task.get_end_time(), "01:00:00");
+        assert_eq!(
+            task.get_start_time(),
+            NaiveTime::from_hms_opt(0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            task.get_end_time(),
+            NaiveTime::from_hms_opt(1, 0, 0).unwrap()
+        );
         assert_eq!(task.get_details(), "This is a test task");
     }
+
+    #[test]
+    fn test_parse_time() {
+        let expected = NaiveTime::from_hms_opt(21, 30, 0);
+
+        assert_eq!(parse_time("21:30"), expected);
+        assert_eq!(parse_time("21:30:00"), expected);
+        assert_eq!(parse_time("9:30 pm"), expected);
+        assert_eq!(parse_time("9:30PM"), expected);
+        assert_eq!(parse_time("25:00"), None);
+        assert_eq!(parse_time("half past nine"), None);
+    }
+
+    #[test]
+    fn test_invalid_time_names_value() {
+        let result = Task::from_record(
+            TaskRecord {
+                id: None,
+                title: "Test Task".to_string(),
+                start_time: "00:00:00".to_string(),
+                end_time: "7 o'clock".to_string(),
+                end_day_offset: None,
+                recurrence: None,
+                reminders: None,
+                end_reminders: None,
+                notify_end: None,
+                silent: false,
+                details: "".to_string(),
+            },
+            Weekday::Mon,
+        );
+
+        assert_eq!(result.unwrap_err(), vec!["invalid end_time \"7 o'clock\""]);
+    }
+
+    #[test]
+    fn test_task_id() {
+        let record = TaskRecord {
+            id: None,
+            title: "Gym".to_string(),
+            start_time: "19:00".to_string(),
+            end_time: "20:00".to_string(),
+            end_day_offset: None,
+            recurrence: None,
+            reminders: None,
+            end_reminders: None,
+            notify_end: None,
+            silent: false,
+            details: "".to_string(),
+        };
+
+        let monday = Task::from_record(record.clone(), Weekday::Mon).unwrap();
+        let tuesday = Task::from_record(record.clone(), Weekday::Tue).unwrap();
+        assert_eq!(
+            monday.get_id(),
+            Task::from_record(record.clone(), Weekday::Mon)
+                .unwrap()
+                .get_id()
+        );
+        assert_ne!(monday.get_id(), tuesday.get_id());
+
+        let explicit = Task::from_record(
+            TaskRecord {
+                id: Some("gym".to_string()),
+                ..record
+            },
+            Weekday::Mon,
+        )
+        .unwrap();
+        assert_eq!(explicit.get_id(), "gym");
+    }
+
+    #[test]
+    fn test_overnight_task() {
+        let record = TaskRecord {
+            id: None,
+            title: "Night shift".to_string(),
+            start_time: "23:30".to_string(),
+            end_time: "01:00".to_string(),
+            end_day_offset: None,
+            recurrence: None,
+            reminders: None,
+            end_reminders: None,
+            notify_end: None,
+            silent: false,
+            details: "".to_string(),
+        };
+
+        let overnight = Task::from_record(record.clone(), Weekday::Fri).unwrap();
+        assert_eq!(overnight.get_end_day_offset(), 1);
+        assert_eq!(overnight.get_duration(), Duration::minutes(90));
+        assert_eq!(format_end_time(&overnight), "01:00 (+1)");
+
+        let two_nights = Task::from_record(
+            TaskRecord {
+                end_day_offset: Some(2),
+                ..record
+            },
+            Weekday::Fri,
+        )
+        .unwrap();
+        assert_eq!(two_nights.get_duration(), Duration::minutes(90 + 24 * 60));
+    }
 }
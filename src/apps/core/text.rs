@@ -0,0 +1,700 @@
+use super::day_of_week::DayOfWeek;
+use super::recurrence::Rule;
+use super::schedule::{Schedule, ScheduleRecord};
+use super::task;
+use super::validation::{Issue, Severity, ValidationReport};
+
+use chrono::{NaiveDate, Weekday};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+// continuation lines are printed this far in
+const INDENT: &str = "    ";
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Clone, PartialEq, Eq)]
+enum Section {
+    Schedule,
+    Day(usize),
+    Date(String),
+}
+
+// a task line and the lines continuing it
+struct PendingTask {
+    indent: usize,
+    section: Section,
+    record: Map<String, Value>,
+    details: Option<String>,
+}
+
+struct Parser {
+    report: ValidationReport,
+    settings: Map<String, Value>,
+    days: Vec<(Weekday, String, Vec<Value>)>,
+    overrides: BTreeMap<String, Map<String, Value>>,
+    section: Section,
+    task: Option<PendingTask>,
+}
+
+// A line-oriented schedule, e.g.
+//
+//     # schedule-wide settings come first
+//     @reminders 10
+//
+//     Monday:
+//     05:30-06:30 お弁当準備 | 卵焼き
+//         おにぎり
+//         @id bento
+//     Tue 23:00-07:00 Night shift
+//
+//     2026-10-26:
+//     @cancel bento
+//     10:00-17:00 Offsite
+//
+// Day and date headers start a section, and a task line may name its day
+// itself. Lines indented past a task line continue its details, or set one of
+// its fields with `@name value`; other `@` lines set fields of the schedule
+// (before any header) or of the date. A continuation line starting with `\` is
+// taken literally.
+pub fn parse_schedule(contents: &str) -> Result<Schedule, ValidationReport> {
+    let mut parser = Parser {
+        report: ValidationReport::new(),
+        settings: Map::new(),
+        days: Vec::new(),
+        overrides: BTreeMap::new(),
+        section: Section::Schedule,
+        task: None,
+    };
+    for (idx, line) in contents.lines().enumerate() {
+        parser.parse_line(idx + 1, line);
+    }
+
+    parser.finish()
+}
+
+impl Parser {
+    fn parse_line(&mut self, number: usize, line: &str) {
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('#') {
+            return;
+        }
+        let offset = line.len() - content.len();
+        let indent = line[..offset].chars().count();
+
+        if let Some(task) = self.task.as_mut().filter(|task| indent > task.indent) {
+            if let Some(setting) = content.strip_prefix('@') {
+                if let Err((at, message)) = parse_task_setting(&mut task.record, setting) {
+                    self.push_error(number, line, offset + 1 + at, &message);
+                }
+                return;
+            }
+
+            let text = content.strip_prefix('\\').unwrap_or(content);
+            match task.details.as_mut() {
+                Some(details) => {
+                    details.push('\n');
+                    details.push_str(text);
+                }
+                None => task.details = Some(text.to_string()),
+            }
+            return;
+        }
+
+        self.flush_task();
+        if let Some(setting) = content.strip_prefix('@') {
+            if let Err((at, message)) = self.parse_section_setting(setting) {
+                self.push_error(number, line, offset + 1 + at, &message);
+            }
+            return;
+        }
+
+        let name = content.strip_suffix(':').unwrap_or(content).trim_end();
+        if let Ok(day_of_week) = name.parse::<DayOfWeek>() {
+            self.section = Section::Day(self.find_day(day_of_week, name));
+            return;
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(name, DATE_FORMAT) {
+            let date = date.format(DATE_FORMAT).to_string();
+            self.overrides.entry(date.clone()).or_default();
+            self.section = Section::Date(date);
+            return;
+        }
+        if content.ends_with(':') && !name.contains(char::is_whitespace) {
+            self.push_error(
+                number,
+                line,
+                offset,
+                &format!("unknown day or date \"{}\"", name),
+            );
+            return;
+        }
+
+        if let Err((at, message)) = self.parse_task_line(content, indent) {
+            self.push_error(number, line, offset + at, &message);
+        }
+    }
+
+    // `[day] start-end[+days] [title] [| details]`
+    fn parse_task_line(&mut self, content: &str, indent: usize) -> Result<(), (usize, String)> {
+        let mut section = self.section.clone();
+        let (mut at, mut token, mut rest) = (0, next_token(content), content);
+
+        if let Ok(day_of_week) = token.parse::<DayOfWeek>() {
+            section = Section::Day(self.find_day(day_of_week, token));
+            rest = rest[token.len()..].trim_start();
+            at = content.len() - rest.len();
+            token = next_token(rest);
+        }
+        if token.is_empty() {
+            return Err((at, "expected a time range like 05:30-06:30".to_string()));
+        }
+        let (start_time, end_time, end_day_offset) =
+            parse_time_range(token).map_err(|(range_at, message)| (at + range_at, message))?;
+        if section == Section::Schedule {
+            return Err((
+                at,
+                "task before any day or date header (start the line with a day, e.g. \"Mon\")"
+                    .to_string(),
+            ));
+        }
+
+        let (title, details) = parse_title(rest[token.len()..].trim_start());
+        let mut record = Map::new();
+        record.insert("title".to_string(), json!(title));
+        record.insert("start_time".to_string(), json!(start_time));
+        record.insert("end_time".to_string(), json!(end_time));
+        if let Some(end_day_offset) = end_day_offset {
+            record.insert("end_day_offset".to_string(), json!(end_day_offset));
+        }
+
+        self.task = Some(PendingTask {
+            indent,
+            section,
+            record,
+            details,
+        });
+        Ok(())
+    }
+
+    fn parse_section_setting(&mut self, setting: &str) -> Result<(), (usize, String)> {
+        let (name, at, value) = split_setting(setting);
+        let settings = match &self.section {
+            Section::Schedule => &mut self.settings,
+            Section::Date(date) => self.overrides.entry(date.clone()).or_default(),
+            Section::Day(_) => {
+                return Err((0, format!("@{} has to be indented under a task", name)))
+            }
+        };
+
+        let parsed = match (&self.section, name) {
+            (Section::Schedule, "reminders" | "end_reminders") => parse_minutes(value),
+            (Section::Schedule, "notify_end") | (Section::Date(_), "replace_all") => {
+                parse_flag(value)
+            }
+            (Section::Date(_), "cancel") => {
+                if value.is_empty() {
+                    return Err((at, "@cancel needs a task id or title".to_string()));
+                }
+                let cancel = settings.entry("cancel").or_insert(json!([]));
+                if let Some(cancel) = cancel.as_array_mut() {
+                    cancel.push(json!(value));
+                }
+                return Ok(());
+            }
+            (Section::Schedule, _) => {
+                return Err((0, format!("unknown schedule setting @{}", name)))
+            }
+            _ => return Err((0, format!("unknown date setting @{}", name))),
+        };
+
+        settings.insert(
+            name.to_string(),
+            parsed.map_err(|(value_at, message)| (at + value_at, message))?,
+        );
+        Ok(())
+    }
+
+    fn find_day(&mut self, day_of_week: DayOfWeek, name: &str) -> usize {
+        let weekday = day_of_week.get_weekday();
+
+        match self.days.iter().rposition(|(day, _, _)| *day == weekday) {
+            Some(idx) => idx,
+            None => {
+                self.days.push((weekday, name.to_string(), Vec::new()));
+                self.days.len() - 1
+            }
+        }
+    }
+
+    fn flush_task(&mut self) {
+        let Some(mut task) = self.task.take() else {
+            return;
+        };
+        task.record.insert(
+            "details".to_string(),
+            json!(task.details.unwrap_or_default()),
+        );
+
+        match task.section {
+            Section::Day(idx) => self.days[idx].2.push(Value::Object(task.record)),
+            Section::Date(date) => {
+                let add = self
+                    .overrides
+                    .entry(date)
+                    .or_default()
+                    .entry("add")
+                    .or_insert(json!([]));
+                if let Some(add) = add.as_array_mut() {
+                    add.push(Value::Object(task.record));
+                }
+            }
+            Section::Schedule => (),
+        }
+    }
+
+    // `at` is a byte offset into `line`
+    fn push_error(&mut self, number: usize, line: &str, at: usize, message: &str) {
+        let column = line[..at.min(line.len())].chars().count() + 1;
+
+        self.report.push(Issue::new(
+            Severity::Error,
+            &format!("line {}, column {}", number, column),
+            message,
+        ));
+    }
+
+    fn finish(mut self) -> Result<Schedule, ValidationReport> {
+        self.flush_task();
+        if self.report.has_errors() {
+            return Err(self.report);
+        }
+
+        let mut record = self.settings;
+        record.insert(
+            "days".to_string(),
+            self.days
+                .into_iter()
+                .map(|(_, name, tasks)| json!({ "day_of_week": name, "tasks": tasks }))
+                .collect(),
+        );
+        record.insert(
+            "overrides".to_string(),
+            self.overrides
+                .into_iter()
+                .map(|(date, date_override)| (date, Value::Object(date_override)))
+                .collect::<Map<_, _>>()
+                .into(),
+        );
+
+        match serde_json::from_value::<ScheduleRecord>(Value::Object(record)) {
+            Ok(record) => Schedule::from_record(record),
+            Err(err) => {
+                self.report
+                    .push(Issue::new(Severity::Error, "", &err.to_string()));
+                Err(self.report)
+            }
+        }
+    }
+}
+
+// errors carry a byte offset into `setting`
+fn parse_task_setting(
+    record: &mut Map<String, Value>,
+    setting: &str,
+) -> Result<(), (usize, String)> {
+    let (name, at, value) = split_setting(setting);
+    let in_value = |(value_at, message): (usize, String)| (at + value_at, message);
+
+    let parsed = match name {
+        "id" if value.is_empty() => return Err((at, "@id needs a value".to_string())),
+        "id" => json!(value),
+        "reminders" | "end_reminders" => parse_minutes(value).map_err(in_value)?,
+        "notify_end" | "silent" => parse_flag(value).map_err(in_value)?,
+        "rule" => {
+            value.parse::<Rule>().map_err(|err| {
+                (
+                    at,
+                    format!("invalid recurrence rule \"{}\": {}", value, err),
+                )
+            })?;
+            json!(value)
+        }
+        "start_date" | "end_date" => {
+            NaiveDate::parse_from_str(value, DATE_FORMAT).map_err(|_| {
+                (
+                    at,
+                    format!("invalid date \"{}\" (expected YYYY-MM-DD)", value),
+                )
+            })?;
+            json!(value)
+        }
+        _ => return Err((0, format!("unknown task setting @{}", name))),
+    };
+
+    if matches!(name, "rule" | "start_date" | "end_date") {
+        let recurrence = record.entry("recurrence").or_insert(json!({}));
+        if let Some(recurrence) = recurrence.as_object_mut() {
+            recurrence.insert(name.to_string(), parsed);
+        }
+    } else {
+        record.insert(name.to_string(), parsed);
+    }
+    Ok(())
+}
+
+// the name, the byte offset of the value and the value
+fn split_setting(setting: &str) -> (&str, usize, &str) {
+    let name = next_token(setting);
+    let rest = &setting[name.len()..];
+    let value = rest.trim_start();
+
+    (name, setting.len() - value.len(), value.trim_end())
+}
+
+fn next_token(text: &str) -> &str {
+    text.split(char::is_whitespace).next().unwrap_or_default()
+}
+
+// `start-end` with an optional `+days` for the end
+fn parse_time_range(range: &str) -> Result<(String, String, Option<u32>), (usize, String)> {
+    let Some((start, end)) = range.split_once('-') else {
+        return Err((
+            0,
+            format!(
+                "expected a time range like 05:30-06:30, found \"{}\"",
+                range
+            ),
+        ));
+    };
+    let end_at = start.len() + 1;
+    let (end, end_day_offset) = match end.split_once('+') {
+        Some((end, days)) => {
+            let days_at = end_at + end.len() + 1;
+            let days = days
+                .parse::<u32>()
+                .map_err(|_| (days_at, format!("invalid day offset \"+{}\"", days)))?;
+            (end, Some(days))
+        }
+        None => (end, None),
+    };
+
+    if task::parse_time(start).is_none() {
+        return Err((0, format!("invalid start time \"{}\"", start)));
+    }
+    if task::parse_time(end).is_none() {
+        return Err((end_at, format!("invalid end time \"{}\"", end)));
+    }
+
+    Ok((start.to_string(), end.to_string(), end_day_offset))
+}
+
+// the title ends at the first `|` that isn't escaped as `\|`
+fn parse_title(text: &str) -> (String, Option<String>) {
+    let mut title = String::new();
+    let mut characters = text.char_indices();
+
+    while let Some((idx, character)) = characters.next() {
+        match character {
+            '\\' => title.extend(characters.next().map(|(_, escaped)| escaped)),
+            '|' => {
+                let details = &text[idx + 1..];
+                let details = details.strip_prefix(' ').unwrap_or(details);
+                return (title.trim().to_string(), Some(details.to_string()));
+            }
+            _ => title.push(character),
+        }
+    }
+
+    (title.trim().to_string(), None)
+}
+
+// minutes separated by spaces or commas; errors carry a byte offset
+fn parse_minutes(value: &str) -> Result<Value, (usize, String)> {
+    let mut minutes = Vec::new();
+    let mut start = None;
+
+    for (idx, character) in value.char_indices().chain([(value.len(), ' ')]) {
+        if !character.is_whitespace() && character != ',' {
+            start.get_or_insert(idx);
+            continue;
+        }
+        if let Some(start) = start.take() {
+            let token = &value[start..idx];
+            minutes.push(
+                token
+                    .parse::<u32>()
+                    .map_err(|_| (start, format!("invalid minutes \"{}\"", token)))?,
+            );
+        }
+    }
+
+    Ok(json!(minutes))
+}
+
+// a flag alone means true
+fn parse_flag(value: &str) -> Result<Value, (usize, String)> {
+    match value {
+        "" | "true" => Ok(json!(true)),
+        "false" => Ok(json!(false)),
+        _ => Err((0, format!("expected true or false, found \"{}\"", value))),
+    }
+}
+
+pub fn format_schedule(schedule: &Schedule) -> Result<String, String> {
+    let schedule = serde_json::to_value(schedule).map_err(|err| err.to_string())?;
+    let mut sections = Vec::new();
+
+    let settings = format_settings(&schedule, &["reminders", "end_reminders", "notify_end"], "");
+    if !settings.is_empty() {
+        sections.push(settings);
+    }
+
+    for day in schedule["days"].as_array().into_iter().flatten() {
+        let mut lines = vec![format!("{}:", get_text(day, "day_of_week"))];
+        for task in day["tasks"].as_array().into_iter().flatten() {
+            lines.extend(format_task(task));
+        }
+        sections.push(lines);
+    }
+
+    for (date, date_override) in schedule["overrides"].as_object().into_iter().flatten() {
+        let mut lines = vec![format!("{}:", date)];
+        lines.extend(format_settings(date_override, &["replace_all"], ""));
+        for reference in date_override["cancel"].as_array().into_iter().flatten() {
+            lines.push(format!(
+                "@cancel {}",
+                reference.as_str().unwrap_or_default()
+            ));
+        }
+        for task in date_override["add"].as_array().into_iter().flatten() {
+            lines.extend(format_task(task));
+        }
+        sections.push(lines);
+    }
+
+    Ok(sections
+        .iter()
+        .map(|lines| lines.join("\n") + "\n")
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn format_task(task: &Value) -> Vec<String> {
+    let mut line = format!(
+        "{}-{}",
+        get_text(task, "start_time"),
+        get_text(task, "end_time")
+    );
+    if let Some(end_day_offset) = task["end_day_offset"].as_u64() {
+        line.push_str(&format!("+{}", end_day_offset));
+    }
+    let title = get_text(task, "title")
+        .replace('\\', "\\\\")
+        .replace('|', "\\|");
+    if !title.is_empty() {
+        line.push(' ');
+        line.push_str(&title);
+    }
+
+    let details = get_text(task, "details");
+    let mut details_lines = details.split('\n');
+    if !details.is_empty() {
+        line.push_str(" | ");
+        line.push_str(details_lines.next().unwrap_or_default());
+    }
+
+    let mut lines = vec![line];
+    for details_line in details_lines {
+        // lines that would read as something else are escaped
+        let is_plain = details_line
+            .chars()
+            .next()
+            .is_some_and(|first| !first.is_whitespace() && !matches!(first, '@' | '#' | '\\'));
+        lines.push(if is_plain {
+            format!("{}{}", INDENT, details_line)
+        } else {
+            format!("{}\\{}", INDENT, details_line)
+        });
+    }
+
+    lines.extend(format_settings(
+        task,
+        &["id", "reminders", "end_reminders", "notify_end", "silent"],
+        INDENT,
+    ));
+    lines.extend(format_settings(
+        &task["recurrence"],
+        &["rule", "start_date", "end_date"],
+        INDENT,
+    ));
+
+    lines
+}
+
+// `@name value` lines for the fields of `object` that are present; lists are
+// space-separated and a true flag is written alone
+fn format_settings(object: &Value, names: &[&str], indent: &str) -> Vec<String> {
+    names
+        .iter()
+        .filter_map(|name| {
+            let value = match object.get(name)? {
+                Value::Bool(true) => String::new(),
+                Value::Array(values) => values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+
+            Some(
+                format!("{}@{} {}", indent, name, value)
+                    .trim_end()
+                    .to_string(),
+            )
+        })
+        .collect()
+}
+
+fn get_text<'a>(object: &'a Value, name: &str) -> &'a str {
+    object[name].as_str().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_json(schedule: &Schedule) -> Value {
+        serde_json::to_value(schedule).unwrap()
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        let schedule = parse_schedule(
+            r#"# weekdays
+@reminders 10, 5
+
+Monday:
+05:30-06:30 お弁当準備 | 卵焼き
+    おにぎり
+    @id bento
+    @reminders 15
+  # comments may sit anywhere
+22:00-06:00+1 Night shift
+    @silent
+    @rule FREQ=WEEKLY;INTERVAL=2
+    @start_date 2026-11-02
+
+Tue 07:00-07:30 Run \| stretch
+
+2026-10-26:
+@cancel bento
+10:00-17:00 Offsite | Bring a laptop
+Mon 12:00-13:00 Lunch
+"#,
+        )
+        .unwrap();
+
+        let expected = serde_json::from_str::<Schedule>(
+            r#"{
+                "days": [
+                    { "day_of_week": "Monday", "tasks": [
+                        { "id": "bento", "title": "お弁当準備", "start_time": "05:30", "end_time": "06:30", "details": "卵焼き\nおにぎり", "reminders": [15] },
+                        { "title": "Night shift", "start_time": "22:00", "end_time": "06:00", "end_day_offset": 1, "silent": true, "details": "",
+                          "recurrence": { "rule": "FREQ=WEEKLY;INTERVAL=2", "start_date": "2026-11-02" } },
+                        { "title": "Lunch", "start_time": "12:00", "end_time": "13:00", "details": "" }
+                    ] },
+                    { "day_of_week": "Tuesday", "tasks": [
+                        { "title": "Run | stretch", "start_time": "07:00", "end_time": "07:30", "details": "" }
+                    ] }
+                ],
+                "overrides": {
+                    "2026-10-26": { "cancel": ["bento"], "add": [
+                        { "title": "Offsite", "start_time": "10:00", "end_time": "17:00", "details": "Bring a laptop" }
+                    ] }
+                },
+                "reminders": [10, 5]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(to_json(&schedule), to_json(&expected));
+    }
+
+    #[test]
+    fn test_format_schedule() {
+        let schedule = serde_json::from_str::<Schedule>(
+            r#"{
+                "days": [
+                    { "day_of_week": "月", "tasks": [
+                        { "id": "bento", "title": "お弁当準備", "start_time": "05:30", "end_time": "06:30",
+                          "details": "卵焼き\n\n  indented\n@home\n#tag | not a title\\", "reminders": [] },
+                        { "title": "A|B\\C", "start_time": "23:00", "end_time": "07:00", "notify_end": false, "details": " lead",
+                          "recurrence": { "end_date": "2026-12-31" } }
+                    ] },
+                    { "day_of_week": "Friday", "tasks": [] }
+                ],
+                "overrides": {
+                    "2026-10-26": { "replace_all": true, "cancel": ["Standup, daily"], "add": [
+                        { "title": "", "start_time": "10:00:30", "end_time": "17:00", "details": "x" }
+                    ] }
+                },
+                "end_reminders": [5],
+                "notify_end": true
+            }"#,
+        )
+        .unwrap();
+
+        let text = format_schedule(&schedule).unwrap();
+        assert_eq!(
+            text,
+            r#"@end_reminders 5
+@notify_end
+
+月曜日:
+05:30-06:30 お弁当準備 | 卵焼き
+    \
+    \  indented
+    \@home
+    \#tag | not a title\
+    @id bento
+    @reminders
+23:00-07:00 A\|B\\C |  lead
+    @notify_end false
+    @end_date 2026-12-31
+
+Friday:
+
+2026-10-26:
+@replace_all
+@cancel Standup, daily
+10:00:30-17:00 | x
+"#
+        );
+        assert_eq!(to_json(&parse_schedule(&text).unwrap()), to_json(&schedule));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let report = parse_schedule(
+            "05:30-06:30 Too early\nMonday:\n05:30-6:7x Bento\n09:00-09:15 Standup\n  @remind 5\n    @reminders 5, soon\n    @rule FREQ=WEEKLY;BYDAY=月\nTue 09:00 Standup\nMondey:\n@cancel x\n",
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            report.to_string(),
+            [
+                "error: line 1, column 1: task before any day or date header (start the line with a day, e.g. \"Mon\")",
+                "error: line 3, column 7: invalid end time \"6:7x\"",
+                "error: line 5, column 4: unknown task setting @remind",
+                "error: line 6, column 19: invalid minutes \"soon\"",
+                "error: line 7, column 11: invalid recurrence rule \"FREQ=WEEKLY;BYDAY=月\": invalid BYDAY=月",
+                "error: line 8, column 5: expected a time range like 05:30-06:30, found \"09:00\"",
+                "error: line 9, column 1: unknown day or date \"Mondey\"",
+                "error: line 10, column 2: @cancel has to be indented under a task",
+            ]
+            .join("\n")
+        );
+    }
+}
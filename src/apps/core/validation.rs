@@ -0,0 +1,117 @@
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Issue {
+    severity: Severity,
+    location: String,
+    message: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ValidationReport {
+    issues: Vec<Issue>,
+}
+
+impl Issue {
+    pub fn new(severity: Severity, location: &str, message: &str) -> Issue {
+        Issue {
+            severity,
+            location: location.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn get_severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn get_description(&self) -> String {
+        if self.location.is_empty() {
+            self.message.clone()
+        } else {
+            format!("{}: {}", self.location, self.message)
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+
+        write!(f, "{}: {}", severity, self.get_description())
+    }
+}
+
+impl ValidationReport {
+    pub fn new() -> ValidationReport {
+        ValidationReport { issues: Vec::new() }
+    }
+
+    pub fn push(&mut self, issue: Issue) {
+        self.issues.push(issue);
+    }
+
+    pub fn extend(&mut self, issues: impl IntoIterator<Item = Issue>) {
+        self.issues.extend(issues);
+    }
+
+    pub fn get_issues(&self) -> &Vec<Issue> {
+        self.issues.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Error)
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = self
+            .issues
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect::<Vec<_>>();
+
+        f.write_str(&lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let mut report = ValidationReport::new();
+        assert!(report.is_empty());
+
+        report.push(Issue::new(
+            Severity::Warning,
+            "Monday: task #0",
+            "empty title",
+        ));
+        assert!(!report.has_errors());
+
+        report.push(Issue::new(Severity::Error, "", "expected value"));
+        assert!(report.has_errors());
+        assert_eq!(
+            report.to_string(),
+            "warning: Monday: task #0: empty title\nerror: expected value"
+        );
+    }
+}
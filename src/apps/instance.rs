@@ -0,0 +1,69 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+
+// held for as long as this process runs; the OS releases the lock when the
+// process exits, so a crashed instance never leaves a stale lock behind
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    // `None` while another instance holds the lock
+    pub fn acquire(lock_path: &Path) -> std::io::Result<Option<InstanceLock>> {
+        if let Some(directory) = lock_path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(lock_path)?;
+
+        match file.try_lock() {
+            Ok(()) => (),
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(err)) => return Err(err),
+        }
+
+        file.set_len(0)?;
+        file.rewind()?;
+        write!(file, "{}", std::process::id())?;
+        file.flush()?;
+
+        Ok(Some(InstanceLock { _file: file }))
+    }
+}
+
+// the process id written by the instance holding the lock, if readable
+pub fn read_pid(lock_path: &Path) -> Option<u32> {
+    let mut contents = String::new();
+    File::open(lock_path)
+        .ok()?
+        .read_to_string(&mut contents)
+        .ok()?;
+
+    contents.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance_lock() {
+        let lock_path =
+            std::env::temp_dir().join(format!("wesche-test-{}.lock", std::process::id()));
+
+        let lock = InstanceLock::acquire(&lock_path).unwrap();
+        assert!(lock.is_some());
+        assert!(InstanceLock::acquire(&lock_path).unwrap().is_none());
+        assert_eq!(read_pid(&lock_path), Some(std::process::id()));
+
+        drop(lock);
+        assert!(InstanceLock::acquire(&lock_path).unwrap().is_some());
+
+        std::fs::remove_file(&lock_path).unwrap();
+    }
+}
@@ -0,0 +1,398 @@
+use super::controller::{Application, NotificationAction};
+use super::core::history::{History, Status};
+use super::core::occurrence::Occurrence;
+
+use async_std::io::BufReader;
+use async_std::os::unix::net::{UnixListener, UnixStream};
+use async_std::prelude::*;
+use async_std::sync::Mutex;
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+const DEFAULT_SNOOZE_MINUTES: u32 = 5;
+
+const DEFAULT_UPCOMING_LIMIT: usize = 5;
+
+// how far ahead `upcoming` and the next task in `status` look
+const UPCOMING_SEARCH_DAYS: i64 = 7;
+
+// one occurrence, by task id and start
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TaskTarget {
+    id: String,
+    start: NaiveDateTime,
+}
+
+// one JSON object per line, e.g. {"command": "snooze", "minutes": 10}; actions
+// without a task apply to the last task notification
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    Status,
+    Reload,
+    OpenViewer,
+    Snooze {
+        #[serde(default)]
+        minutes: Option<u32>,
+        #[serde(default)]
+        task: Option<TaskTarget>,
+    },
+    Done {
+        #[serde(default)]
+        task: Option<TaskTarget>,
+    },
+    Skip {
+        #[serde(default)]
+        task: Option<TaskTarget>,
+    },
+    DoNotDisturb {
+        minutes: u32,
+    },
+    DoNotDisturbOff,
+    Upcoming {
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+    Quit,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TaskReport {
+    id: String,
+    title: String,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    status: Status,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StatusReport {
+    now: NaiveDateTime,
+    is_quiet: bool,
+    do_not_disturb_until: Option<NaiveDateTime>,
+    current: Vec<TaskReport>,
+    next: Option<TaskReport>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Response {
+    ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<StatusReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tasks: Option<Vec<TaskReport>>,
+}
+
+impl TaskReport {
+    fn from(occurrence: &Occurrence, history: &History, now: NaiveDateTime) -> TaskReport {
+        TaskReport {
+            id: occurrence.get_task().get_id().to_string(),
+            title: occurrence.get_task().get_title().to_string(),
+            start: occurrence.get_start(),
+            end: occurrence.get_end(),
+            status: history.get_status(occurrence, now),
+        }
+    }
+}
+
+impl Response {
+    fn ok() -> Response {
+        Response {
+            ok: true,
+            error: None,
+            status: None,
+            tasks: None,
+        }
+    }
+
+    fn error(message: &str) -> Response {
+        Response {
+            ok: false,
+            error: Some(message.to_string()),
+            ..Response::ok()
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.ok
+    }
+
+    pub fn get_error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+// a socket left behind by a process that is gone is replaced, but one that
+// still answers is not
+pub async fn bind(socket_path: &Path) -> std::io::Result<UnixListener> {
+    if socket_path.exists() {
+        if UnixStream::connect(socket_path).await.is_ok() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AddrInUse,
+                format!("{} is in use by another instance", socket_path.display()),
+            ));
+        }
+        async_std::fs::remove_file(socket_path).await?;
+    }
+    if let Some(directory) = socket_path.parent() {
+        async_std::fs::create_dir_all(directory).await?;
+    }
+
+    UnixListener::bind(socket_path).await
+}
+
+pub async fn serve(listener: UnixListener, application: Arc<Mutex<Application>>) {
+    let mut incoming = listener.incoming();
+
+    while let Some(stream) = incoming.next().await {
+        match stream {
+            Ok(stream) => {
+                let application = application.clone();
+                async_std::task::spawn(async move {
+                    if let Err(err) = serve_connection(stream, application).await {
+                        dbg!(err);
+                    }
+                });
+            }
+            Err(err) => {
+                dbg!(err);
+            }
+        }
+    }
+}
+
+async fn serve_connection(
+    stream: UnixStream,
+    application: Arc<Mutex<Application>>,
+) -> std::io::Result<()> {
+    let mut writer = stream.clone();
+    let mut lines = BufReader::new(stream).lines();
+
+    while let Some(line) = lines.next().await {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => handle_request(&mut *application.lock().await, request).await,
+            Err(err) => Response::error(&format!("invalid request: {}", err)),
+        };
+        let mut contents = serde_json::to_string(&response)?;
+        contents.push('\n');
+        writer.write_all(contents.as_bytes()).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn handle_request(application: &mut Application, request: Request) -> Response {
+    let to_target = |task: Option<TaskTarget>| task.map(|task| (task.id, task.start));
+
+    match request {
+        Request::Status => {
+            let now = application.get_clock().now();
+            let schedule = application.get_schedule();
+            let schedule = schedule.lock().await;
+            let history = application.get_history();
+
+            Response {
+                status: Some(StatusReport {
+                    now,
+                    is_quiet: application.is_quiet(now),
+                    do_not_disturb_until: application.get_do_not_disturb_until(),
+                    current: schedule
+                        .get_occurrences_around(now)
+                        .iter()
+                        .filter(|occurrence| {
+                            occurrence.get_start() <= now && now < occurrence.get_end()
+                        })
+                        .map(|occurrence| TaskReport::from(occurrence, history, now))
+                        .collect(),
+                    next: schedule
+                        .get_upcoming_occurrences(now, now + Duration::days(UPCOMING_SEARCH_DAYS))
+                        .first()
+                        .map(|occurrence| TaskReport::from(occurrence, history, now)),
+                }),
+                ..Response::ok()
+            }
+        }
+        Request::Reload => match application.update_contents().await {
+            Ok(()) => Response::ok(),
+            Err(err) => Response::error(&err),
+        },
+        Request::OpenViewer => {
+            if !application.has_viewer() {
+                return Response::error("running without a viewer");
+            }
+            application.open_viewer();
+            Response::ok()
+        }
+        Request::Snooze { minutes, task } => {
+            let minutes = minutes.unwrap_or(DEFAULT_SNOOZE_MINUTES);
+            if minutes == 0 {
+                return Response::error("minutes must be at least 1");
+            }
+            application.request_action(to_target(task), NotificationAction::Snooze { minutes });
+            Response::ok()
+        }
+        Request::Done { task } => {
+            application.request_action(to_target(task), NotificationAction::Done);
+            Response::ok()
+        }
+        Request::Skip { task } => {
+            application.request_action(to_target(task), NotificationAction::Skip);
+            Response::ok()
+        }
+        Request::DoNotDisturb { minutes } => {
+            if minutes == 0 {
+                return Response::error("minutes must be at least 1");
+            }
+            application.request_do_not_disturb(Some(minutes));
+            Response::ok()
+        }
+        Request::DoNotDisturbOff => {
+            application.request_do_not_disturb(None);
+            Response::ok()
+        }
+        Request::Upcoming { limit } => {
+            let now = application.get_clock().now();
+            let schedule = application.get_schedule();
+            let schedule = schedule.lock().await;
+
+            Response {
+                tasks: Some(
+                    schedule
+                        .get_upcoming_occurrences(now, now + Duration::days(UPCOMING_SEARCH_DAYS))
+                        .iter()
+                        .take(limit.unwrap_or(DEFAULT_UPCOMING_LIMIT))
+                        .map(|occurrence| {
+                            TaskReport::from(occurrence, application.get_history(), now)
+                        })
+                        .collect(),
+                ),
+                ..Response::ok()
+            }
+        }
+        Request::Quit => {
+            application.shutdown();
+            Response::ok()
+        }
+    }
+}
+
+pub async fn send_request(socket_path: &Path, request: &Request) -> std::io::Result<Response> {
+    let stream = UnixStream::connect(socket_path).await?;
+    let mut writer = stream.clone();
+
+    let mut contents = serde_json::to_string(request)?;
+    contents.push('\n');
+    writer.write_all(contents.as_bytes()).await?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).await?;
+
+    Ok(serde_json::from_str(&line)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::clock::FixedClock;
+    use super::super::config::Config;
+    use super::super::core::schedule::Schedule;
+    use super::super::notifier::RecordingNotifier;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_request_format() {
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"command": "snooze", "minutes": 10}"#).unwrap(),
+            Request::Snooze {
+                minutes: Some(10),
+                task: None
+            }
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(
+                r#"{"command": "done", "task": {"id": "standup", "start": "2026-10-19T09:00:00"}}"#
+            )
+            .unwrap(),
+            Request::Done {
+                task: Some(TaskTarget {
+                    id: "standup".to_string(),
+                    start: NaiveDate::from_ymd_opt(2026, 10, 19)
+                        .unwrap()
+                        .and_hms_opt(9, 0, 0)
+                        .unwrap(),
+                })
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&Request::DoNotDisturbOff).unwrap(),
+            r#"{"command":"do_not_disturb_off"}"#
+        );
+        // a missing duration must not be read as turning do-not-disturb off
+        assert!(serde_json::from_str::<Request>(r#"{"command": "do_not_disturb"}"#).is_err());
+        assert!(serde_json::from_str::<Request>(r#"{"command": "dance"}"#).is_err());
+    }
+
+    #[async_std::test]
+    async fn test_socket_round_trip() {
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let clock = Arc::new(FixedClock::new(monday.and_hms_opt(9, 5, 0).unwrap()));
+        let application = Arc::new(Mutex::new(Application::with_backends(
+            Config::default(),
+            clock,
+            Arc::new(RecordingNotifier::new()),
+        )));
+        *application.lock().await.get_schedule().lock().await = serde_json::from_str::<Schedule>(
+            r#"{ "days": [{ "day_of_week": "Monday", "tasks": [
+                { "id": "standup", "title": "Standup", "start_time": "09:00", "end_time": "09:15", "details": "" },
+                { "id": "review", "title": "Review", "start_time": "10:00", "end_time": "10:30", "details": "" }
+            ] }] }"#,
+        )
+        .unwrap();
+
+        let socket_path =
+            std::env::temp_dir().join(format!("wesche-test-{}.sock", std::process::id()));
+        let listener = bind(&socket_path).await.unwrap();
+        async_std::task::spawn(serve(listener, application.clone()));
+        assert!(bind(&socket_path).await.is_err());
+
+        let status = send_request(&socket_path, &Request::Status)
+            .await
+            .unwrap()
+            .status
+            .unwrap();
+        assert!(!status.is_quiet);
+        assert_eq!(status.current.len(), 1);
+        assert_eq!(status.current[0].id, "standup");
+        assert_eq!(status.current[0].status, Status::InProgress);
+        assert_eq!(status.next.unwrap().id, "review");
+
+        let tasks = send_request(&socket_path, &Request::Upcoming { limit: Some(1) })
+            .await
+            .unwrap()
+            .tasks
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Review");
+
+        let response = send_request(&socket_path, &Request::OpenViewer)
+            .await
+            .unwrap();
+        assert_eq!(response.get_error(), Some("running without a viewer"));
+
+        let response = send_request(&socket_path, &Request::Quit).await.unwrap();
+        assert!(response.is_ok());
+        assert!(application.lock().await.check_shutdown());
+
+        async_std::fs::remove_file(&socket_path).await.unwrap();
+    }
+}
@@ -1,3 +1,10 @@
+pub mod cli;
+mod clock;
+pub mod config;
 pub mod controller;
 mod core;
+pub mod instance;
+#[cfg(unix)]
+pub mod ipc;
+mod notifier;
 mod view;
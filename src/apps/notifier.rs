@@ -0,0 +1,433 @@
+use super::core::notification::NotificationAction;
+
+use notify_rust::Notification;
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[cfg(target_os = "macos")]
+const SOUND_NAME: &str = "Submarine";
+
+#[cfg(all(unix, not(target_os = "macos")))]
+const SOUND_NAME: &str = "message-new-instant";
+
+#[cfg(target_os = "windows")]
+const SOUND_NAME: &str = "Mail";
+
+pub type ActionHandler = Box<dyn FnOnce(NotificationAction) + Send>;
+
+pub trait Notifier: Send + Sync {
+    fn notify(&self, summary: &str, body: &str) -> Result<(), String>;
+
+    // without sound, for backends that have one
+    fn notify_silently(&self, summary: &str, body: &str) -> Result<(), String> {
+        self.notify(summary, body)
+    }
+
+    fn supports_actions(&self) -> bool {
+        false
+    }
+
+    // failures found after `notify` returned, e.g. a command that exited with
+    // an error; each is returned once
+    fn take_failures(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    // backends without buttons show the notification and drop the handler
+    fn notify_with_actions(
+        &self,
+        summary: &str,
+        body: &str,
+        _actions: &[NotificationAction],
+        _on_action: ActionHandler,
+    ) -> Result<(), String> {
+        self.notify(summary, body)
+    }
+}
+
+pub struct DesktopNotifier;
+
+impl DesktopNotifier {
+    fn build(summary: &str, body: &str) -> Notification {
+        let mut notification = Notification::new();
+        notification.summary(summary).body(body);
+        notification
+    }
+}
+
+impl Notifier for DesktopNotifier {
+    fn notify(&self, summary: &str, body: &str) -> Result<(), String> {
+        DesktopNotifier::build(summary, body)
+            .sound_name(SOUND_NAME)
+            .show()
+            .map(|_| ())
+            .map_err(|err| format!("desktop: {}", err))
+    }
+
+    fn notify_silently(&self, summary: &str, body: &str) -> Result<(), String> {
+        DesktopNotifier::build(summary, body)
+            .show()
+            .map(|_| ())
+            .map_err(|err| format!("desktop: {}", err))
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn supports_actions(&self) -> bool {
+        notify_rust::get_capabilities()
+            .map(|capabilities| {
+                capabilities
+                    .iter()
+                    .any(|capability| capability == "actions")
+            })
+            .unwrap_or(false)
+    }
+
+    // the handle is waited on in its own thread, as waiting blocks until the
+    // notification is clicked or closed
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn notify_with_actions(
+        &self,
+        summary: &str,
+        body: &str,
+        actions: &[NotificationAction],
+        on_action: ActionHandler,
+    ) -> Result<(), String> {
+        let mut notification = DesktopNotifier::build(summary, body);
+        notification.sound_name(SOUND_NAME);
+        for action in actions {
+            notification.action(&action.get_id(), &action.get_label());
+        }
+        let handle = notification
+            .show()
+            .map_err(|err| format!("desktop: {}", err))?;
+
+        thread::spawn(move || {
+            handle.wait_for_action(|id| {
+                if let Some(action) = NotificationAction::from_id(id) {
+                    on_action(action);
+                }
+            })
+        });
+
+        Ok(())
+    }
+}
+
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn notify(&self, summary: &str, body: &str) -> Result<(), String> {
+        println!("{}\n{}", summary, body);
+        Ok(())
+    }
+}
+
+// runs `program args.. summary body`
+pub struct CommandNotifier {
+    program: String,
+    args: Vec<String>,
+    failure_sender: Sender<String>,
+    failure_receiver: Mutex<Receiver<String>>,
+}
+
+impl CommandNotifier {
+    pub fn new(program: &str, args: &[String]) -> CommandNotifier {
+        let (failure_sender, failure_receiver) = mpsc::channel();
+
+        CommandNotifier {
+            program: program.to_string(),
+            args: args.to_vec(),
+            failure_sender,
+            failure_receiver: Mutex::new(failure_receiver),
+        }
+    }
+}
+
+impl Notifier for CommandNotifier {
+    // the command is waited on in its own thread, as notifying happens with
+    // the application locked; a failure to start it is returned, and a failed
+    // exit comes back through `take_failures`
+    fn notify(&self, summary: &str, body: &str) -> Result<(), String> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .arg(summary)
+            .arg(body)
+            .spawn()
+            .map_err(|err| format!("command \"{}\": {}", self.program, err))?;
+
+        let program = self.program.clone();
+        let failure_sender = self.failure_sender.clone();
+        thread::spawn(move || {
+            let failure = match child.wait() {
+                Ok(status) if status.success() => return,
+                Ok(status) => format!("command \"{}\": {}", program, status),
+                Err(err) => format!("command \"{}\": {}", program, err),
+            };
+            let _ = failure_sender.send(failure);
+        });
+
+        Ok(())
+    }
+
+    fn take_failures(&self) -> Vec<String> {
+        self.failure_receiver.lock().unwrap().try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+pub struct RecordingNotifier {
+    notifications: Mutex<Vec<(String, String)>>,
+    silent_summaries: Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+impl RecordingNotifier {
+    pub fn new() -> RecordingNotifier {
+        RecordingNotifier {
+            notifications: Mutex::new(Vec::new()),
+            silent_summaries: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn get_notifications(&self) -> Vec<(String, String)> {
+        self.notifications.lock().unwrap().clone()
+    }
+
+    pub fn get_silent_summaries(&self) -> Vec<String> {
+        self.silent_summaries.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl Notifier for RecordingNotifier {
+    fn notify(&self, summary: &str, body: &str) -> Result<(), String> {
+        self.notifications
+            .lock()
+            .unwrap()
+            .push((summary.to_string(), body.to_string()));
+        Ok(())
+    }
+
+    fn notify_silently(&self, summary: &str, body: &str) -> Result<(), String> {
+        self.silent_summaries
+            .lock()
+            .unwrap()
+            .push(summary.to_string());
+        self.notify(summary, body)
+    }
+}
+
+// sends to every backend even if an earlier one fails
+pub struct MultiNotifier {
+    notifiers: Vec<Arc<dyn Notifier>>,
+}
+
+impl MultiNotifier {
+    pub fn new(notifiers: Vec<Arc<dyn Notifier>>) -> MultiNotifier {
+        MultiNotifier { notifiers }
+    }
+}
+
+impl Notifier for MultiNotifier {
+    fn notify(&self, summary: &str, body: &str) -> Result<(), String> {
+        let errors = self
+            .notifiers
+            .iter()
+            .filter_map(|notifier| notifier.notify(summary, body).err())
+            .collect::<Vec<_>>();
+
+        join_errors(errors)
+    }
+
+    fn notify_silently(&self, summary: &str, body: &str) -> Result<(), String> {
+        let errors = self
+            .notifiers
+            .iter()
+            .filter_map(|notifier| notifier.notify_silently(summary, body).err())
+            .collect::<Vec<_>>();
+
+        join_errors(errors)
+    }
+
+    fn supports_actions(&self) -> bool {
+        self.notifiers
+            .iter()
+            .any(|notifier| notifier.supports_actions())
+    }
+
+    fn take_failures(&self) -> Vec<String> {
+        self.notifiers
+            .iter()
+            .flat_map(|notifier| notifier.take_failures())
+            .collect()
+    }
+
+    // only the first backend with buttons gets the handler
+    fn notify_with_actions(
+        &self,
+        summary: &str,
+        body: &str,
+        actions: &[NotificationAction],
+        on_action: ActionHandler,
+    ) -> Result<(), String> {
+        let mut on_action = Some(on_action);
+
+        let errors = self
+            .notifiers
+            .iter()
+            .filter_map(|notifier| {
+                match on_action.take_if(|_| notifier.supports_actions()) {
+                    Some(on_action) => {
+                        notifier.notify_with_actions(summary, body, actions, on_action)
+                    }
+                    None => notifier.notify(summary, body),
+                }
+                .err()
+            })
+            .collect::<Vec<_>>();
+
+        join_errors(errors)
+    }
+}
+
+fn join_errors(errors: Vec<String>) -> Result<(), String> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+// the named backends ("desktop", "stdout" or "command"); "command" runs
+// `command` split on whitespace
+pub fn build_notifier(names: &[String], command: &str) -> Arc<dyn Notifier> {
+    Arc::new(MultiNotifier::new(build_notifiers(names, command)))
+}
+
+fn build_notifiers(names: &[String], command: &str) -> Vec<Arc<dyn Notifier>> {
+    let mut notifiers: Vec<Arc<dyn Notifier>> = Vec::new();
+
+    for name in names
+        .iter()
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+    {
+        match name {
+            "desktop" => notifiers.push(Arc::new(DesktopNotifier)),
+            "stdout" | "log" => notifiers.push(Arc::new(LogNotifier)),
+            "command" => match command.split_whitespace().collect::<Vec<_>>().split_first() {
+                Some((program, args)) => notifiers.push(Arc::new(CommandNotifier::new(
+                    program,
+                    &args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>(),
+                ))),
+                None => {
+                    dbg!("No notify command is set, skipping the command notifier");
+                }
+            },
+            name => {
+                dbg!(format!("Unknown notifier \"{}\"", name));
+            }
+        }
+    }
+
+    notifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FailingNotifier;
+
+    impl Notifier for FailingNotifier {
+        fn notify(&self, _summary: &str, _body: &str) -> Result<(), String> {
+            Err("unavailable".to_string())
+        }
+    }
+
+    #[test]
+    fn test_multi_notifier_reports_failures() {
+        let recorder = Arc::new(RecordingNotifier::new());
+        let notifier = MultiNotifier::new(vec![
+            Arc::new(FailingNotifier),
+            recorder.clone(),
+            Arc::new(FailingNotifier),
+        ]);
+
+        assert_eq!(
+            notifier.notify("★ Standup", "body"),
+            Err("unavailable; unavailable".to_string())
+        );
+        assert_eq!(
+            recorder.get_notifications(),
+            vec![("★ Standup".to_string(), "body".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_build_notifiers() {
+        let names = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            build_notifiers(&names(&["desktop", " stdout"]), "").len(),
+            2
+        );
+        assert_eq!(
+            build_notifiers(&names(&["command", "unknown"]), "").len(),
+            0
+        );
+        assert_eq!(
+            build_notifiers(&names(&["command"]), "notify-send -u low").len(),
+            1
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_notifier() {
+        assert!(CommandNotifier::new("true", &[]).notify("s", "b").is_ok());
+        assert!(CommandNotifier::new("/nonexistent/wesche-notify", &[])
+            .notify("s", "b")
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_notifier_reports_failed_exit() {
+        let notifier = MultiNotifier::new(vec![
+            Arc::new(CommandNotifier::new("true", &[])),
+            Arc::new(CommandNotifier::new("false", &[])),
+        ]);
+        notifier.notify("s", "b").unwrap();
+
+        let mut failures = Vec::new();
+        for _ in 0..100 {
+            failures.extend(notifier.take_failures());
+            if !failures.is_empty() {
+                break;
+            }
+            thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert_eq!(failures, ["command \"false\": exit status: 1"]);
+        assert!(notifier.take_failures().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_notifier_does_not_wait() {
+        let started_at = std::time::Instant::now();
+        assert!(
+            CommandNotifier::new("sh", &["-c".to_string(), "sleep 5".to_string()])
+                .notify("s", "b")
+                .is_ok()
+        );
+        assert!(started_at.elapsed() < std::time::Duration::from_secs(1));
+    }
+}
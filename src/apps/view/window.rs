@@ -1,13 +1,19 @@
-use super::super::core::day::Day;
+use super::super::core::history::{History, OccurrenceKey, Status};
+use super::super::core::notification::NotificationAction;
+use super::super::core::occurrence::Occurrence;
 use super::super::core::schedule::Schedule;
-use super::super::core::task::Task;
+use super::super::core::task;
 
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
 use druid::text::FontDescriptor;
 use druid::widget::{Align, Button, Flex, Label, List, ViewSwitcher};
 use druid::{
     im, lens, AppLauncher, Color, Data, FontFamily, FontStyle, Lens, LensExt, Widget, WidgetExt,
     WindowDesc,
 };
+use std::sync::Arc;
+
+pub type ActionHandler = Arc<dyn Fn(OccurrenceKey, NotificationAction) + Send + Sync>;
 
 #[derive(Data, Clone, Lens)]
 struct TaskForDruid {
@@ -16,6 +22,9 @@ struct TaskForDruid {
     pub start_time: String,
     pub end_time: String,
     pub details: String,
+    pub status: String,
+    pub key: Arc<OccurrenceKey>,
+    pub on_action: ActionHandler,
     pub is_clicked: bool,
 }
 
@@ -41,42 +50,105 @@ struct App {
 }
 
 impl TaskForDruid {
-    fn from(task: &Task, id: usize) -> TaskForDruid {
+    fn from(
+        occurrence: &Occurrence,
+        status: Status,
+        on_action: &ActionHandler,
+        id: usize,
+    ) -> TaskForDruid {
+        let task = occurrence.get_task();
+
         TaskForDruid {
             id,
             title: task.get_title().to_string(),
-            start_time: task.get_start_time().to_string(),
-            end_time: task.get_end_time().to_string(),
+            start_time: task::format_time(&task.get_start_time()),
+            end_time: task::format_end_time(task),
             details: task.get_details().to_string(),
+            status: status.to_string(),
+            key: Arc::new((task.get_id().to_string(), occurrence.get_start())),
+            on_action: on_action.clone(),
             is_clicked: false,
         }
     }
+
+    // a task started on an earlier date that is still running on the shown date
+    fn from_continuing(
+        occurrence: &Occurrence,
+        status: Status,
+        on_action: &ActionHandler,
+        date: NaiveDate,
+        id: usize,
+    ) -> TaskForDruid {
+        let remaining_days = (occurrence.get_end().date() - date).num_days();
+        let end_time = task::format_time(&occurrence.get_task().get_end_time());
+
+        TaskForDruid {
+            start_time: occurrence.get_start().format("(%m/%d) %H:%M").to_string(),
+            end_time: match remaining_days {
+                0 => end_time,
+                _ => format!("{} (+{})", end_time, remaining_days),
+            },
+            ..TaskForDruid::from(occurrence, status, on_action, id)
+        }
+    }
 }
 
 impl DayForDruid {
-    fn from(day: &Day, id: usize) -> DayForDruid {
+    fn from(
+        schedule: &Schedule,
+        history: &History,
+        on_action: &ActionHandler,
+        date: NaiveDate,
+        now: NaiveDateTime,
+        id: usize,
+    ) -> DayForDruid {
+        let mut tasks = im::Vector::new();
+
+        for occurrence in schedule.get_continuing_occurrences(date) {
+            tasks.push_back(TaskForDruid::from_continuing(
+                &occurrence,
+                history.get_status(&occurrence, now),
+                on_action,
+                date,
+                tasks.len(),
+            ));
+        }
+        for occurrence in schedule.get_occurrences_on(date) {
+            tasks.push_back(TaskForDruid::from(
+                &occurrence,
+                history.get_status(&occurrence, now),
+                on_action,
+                tasks.len(),
+            ));
+        }
+
         DayForDruid {
             id,
-            day_of_week: day.get_day_of_week().to_string(),
-            tasks: day
-                .get_tasks()
-                .iter()
-                .enumerate()
-                .map(|(idx, task)| TaskForDruid::from(task, idx))
-                .collect(),
+            day_of_week: format!(
+                "{}\n{}",
+                schedule.get_day_of_week(date.weekday()),
+                date.format("%m/%d")
+            ),
+            tasks,
             is_clicked: false,
         }
     }
 }
 
 impl ScheduleForDruid {
-    fn from(schedule: &Schedule) -> ScheduleForDruid {
+    fn from(
+        schedule: &Schedule,
+        history: &History,
+        on_action: &ActionHandler,
+        now: NaiveDateTime,
+    ) -> ScheduleForDruid {
         ScheduleForDruid {
-            days: schedule
-                .get_days()
-                .iter()
+            days: now
+                .date()
+                .iter_days()
+                .take(7)
                 .enumerate()
-                .map(|(idx, day)| DayForDruid::from(day, idx))
+                .map(|(idx, date)| DayForDruid::from(schedule, history, on_action, date, now, idx))
                 .collect(),
         }
     }
@@ -93,14 +165,21 @@ impl App {
     }
 }
 
-pub fn open_window(window_title: &str, schedule: Schedule) {
+// the week shown starts on the date of `now`
+pub fn open_window(
+    window_title: &str,
+    schedule: Schedule,
+    history: History,
+    now: NaiveDateTime,
+    on_action: ActionHandler,
+) {
     let main_window = WindowDesc::new(build_ui())
         .title(window_title)
         .window_size((820.0, 600.0))
         .resizable(false)
         .transparent(true);
 
-    let app = App::from(ScheduleForDruid::from(&schedule));
+    let app = App::from(ScheduleForDruid::from(&schedule, &history, &on_action, now));
 
     AppLauncher::with_window(main_window)
         .launch(app)
@@ -177,7 +256,10 @@ fn build_today_ui(selected_day_index: usize) -> impl Widget<App> {
                             )
                             .with_child(
                                 Label::new(|task: &TaskForDruid, _env: &_| {
-                                    format!("⌛ {} ～ {}", task.start_time, task.end_time)
+                                    format!(
+                                        "⌛ {} ～ {}  [{}]",
+                                        task.start_time, task.end_time, task.status
+                                    )
                                 })
                                 .with_text_color(Color::BLACK)
                                 .with_text_size(20.0)
@@ -248,7 +330,10 @@ fn build_task_ui(selected_day_index: usize, selected_task_index: usize) -> impl
                 )
                 .with_child(
                     Label::new(|task: &TaskForDruid, _env: &_| {
-                        format!("⌛ {} ～ {}", task.start_time, task.end_time)
+                        format!(
+                            "⌛ {} ～ {}  [{}]",
+                            task.start_time, task.end_time, task.status
+                        )
                     })
                     .with_text_color(Color::BLACK)
                     .with_text_size(18.0)
@@ -269,7 +354,17 @@ fn build_task_ui(selected_day_index: usize, selected_task_index: usize) -> impl
                             .background(Color::rgba8(230, 245, 255, 180)),
                         )
                         .scroll()
-                        .fix_height(430.0),
+                        .fix_height(390.0),
+                )
+                .with_child(
+                    Flex::row()
+                        .with_child(build_action_button(NotificationAction::Done, Status::Done))
+                        .with_spacer(10.0)
+                        .with_child(build_action_button(
+                            NotificationAction::Skip,
+                            Status::Skipped,
+                        ))
+                        .padding(5.0),
                 )
                 .fix_size(500.0, 600.0)
                 .background(Color::rgba8(255, 255, 255, 230))
@@ -287,6 +382,14 @@ fn build_task_ui(selected_day_index: usize, selected_task_index: usize) -> impl
         .background(Color::rgba8(250, 240, 220, 180))
 }
 
+// sends the action to the controller and shows the new status right away
+fn build_action_button(action: NotificationAction, status: Status) -> impl Widget<TaskForDruid> {
+    Button::new(action.get_label()).on_click(move |_ctx, task: &mut TaskForDruid, _env| {
+        (task.on_action)(task.key.as_ref().clone(), action);
+        task.status = status.to_string();
+    })
+}
+
 fn build_ui() -> impl Widget<App> {
     let view_switcher = ViewSwitcher::new(
         |app: &App, _env| app.view_mode,
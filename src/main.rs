@@ -5,26 +5,79 @@ use async_std::sync::Mutex;
 use async_std::task;
 
 mod apps;
+use apps::cli::{self, Arguments, Command};
+use apps::config::Config;
 use apps::controller::{self, AsyncLoopInterface};
+use apps::instance::{self, InstanceLock};
+#[cfg(unix)]
+use apps::ipc::{self, Request};
 
 #[async_std::main]
 async fn main() -> std::io::Result<()> {
-    if env::var("RUNNING_WITH_CARGO").is_ok() {
-        if cfg!(debug_assertions) {
+    // dev builds read the project's assets; release builds keep their files
+    // in the XDG directories instead
+    if cfg!(debug_assertions) {
+        if env::var("RUNNING_WITH_CARGO").is_ok() {
             dbg!("Running with Cargo");
-        }
-        env::set_var("PROJECT_ROOT", env::var("CARGO_MANIFEST_DIR").unwrap());
-    } else {
-        if cfg!(debug_assertions) {
+            env::set_var("PROJECT_ROOT", env::var("CARGO_MANIFEST_DIR").unwrap());
+        } else {
             dbg!("Running directly");
+            env::set_var(
+                "PROJECT_ROOT",
+                env::current_dir().unwrap().to_str().unwrap(),
+            );
+        }
+    }
+
+    let arguments = match Arguments::parse(env::args().skip(1)) {
+        Ok(arguments) => arguments,
+        Err(err) => {
+            attach_console();
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            std::process::exit(2);
         }
-        env::set_var(
-            "PROJECT_ROOT",
-            env::current_dir().unwrap().to_str().unwrap(),
-        );
+    };
+    if !matches!(arguments.get_command(), Command::Run | Command::Daemon) {
+        attach_console();
     }
+    let config = Config::load(arguments.get_config_path().map(|path| path.as_path()))
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
 
-    let application_controller = Arc::new(Mutex::new(controller::Application::new()));
+    match arguments.get_command() {
+        Command::Run => run(config, false).await,
+        Command::Daemon => run(config, true).await,
+        command => std::process::exit(cli::execute(command, config).await?),
+    }
+}
+
+// release builds on Windows have no console of their own, so the subcommands
+// print to the one they were started from, if any; `run` and `daemon` don't
+// attach, as closing that console would end them
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+// the tray app with the scheduler, file observer and viewer; headless, or
+// when no system tray is available, only the scheduler and the file observer
+// run, e.g. as a systemd user service
+async fn run(config: Config, is_headless: bool) -> std::io::Result<()> {
+    let Some(_instance_lock) = InstanceLock::acquire(&config.get_lock_path())? else {
+        return hand_off(&config, is_headless).await;
+    };
+
+    let should_watch_schedule = config.should_watch_schedule();
+    #[cfg(unix)]
+    let socket_path = config.get_socket_path().to_path_buf();
+
+    let application_controller = Arc::new(Mutex::new(controller::Application::new(config)));
 
     {
         let mut application_controller = application_controller.lock().await;
@@ -34,78 +87,205 @@ async fn main() -> std::io::Result<()> {
     let file_observer_handle = {
         let application_controller = application_controller.clone();
         task::spawn(async move {
-            application_controller
-                .lock()
-                .await
-                .start_observer()
-                .await
-                .unwrap()
+            if should_watch_schedule {
+                application_controller
+                    .lock()
+                    .await
+                    .start_observer()
+                    .await
+                    .unwrap()
+            }
         })
     };
 
+    // the control socket is optional, so failing to bind it only gets logged
+    #[cfg(unix)]
+    let socket_path = match ipc::bind(&socket_path).await {
+        Ok(listener) => {
+            task::spawn(ipc::serve(listener, application_controller.clone()));
+            Some(socket_path)
+        }
+        Err(err) => {
+            dbg!(err);
+            None
+        }
+    };
+
     let application_loop_handle = {
         let application_controller = application_controller.clone();
         task::spawn(async move { application_controller.async_loop().await })
     };
 
-    let opening_viewer_handle = {
+    let system_tray = if is_headless {
+        None
+    } else {
+        match build_system_tray(&application_controller).await {
+            Ok(system_tray) => Some(system_tray),
+            Err(err) => {
+                dbg!("Failed to create the system tray, running headless");
+                dbg!(err.to_string());
+                None
+            }
+        }
+    };
+
+    application_controller
+        .lock()
+        .await
+        .set_has_viewer(system_tray.is_some());
+
+    let opening_viewer_handle = system_tray.map(|mut system_tray| {
+        async_std::task::spawn(async move {
+            system_tray
+                .wait_for_message()
+                .expect("Failed to wait for sytem_tray message");
+
+            system_tray
+                .shutdown()
+                .expect("Failed to shutdown system tray");
+        });
+
         let application_controller = application_controller.clone();
         task::spawn(async move { application_controller.wait_for_open_viewer().await })
-    };
+    });
 
-    let mut system_tray = systray::Application::new().expect("Failed to create system tray");
+    file_observer_handle.await;
+    application_loop_handle.await;
+    if let Some(opening_viewer_handle) = opening_viewer_handle {
+        opening_viewer_handle.await;
+    }
+    #[cfg(unix)]
+    if let Some(socket_path) = socket_path {
+        let _ = async_std::fs::remove_file(socket_path).await;
+    }
+
+    Ok(())
+}
+
+// a second launch asks the running instance to open the viewer, or to reload
+// the schedule when started headless, instead of running twice
+async fn hand_off(config: &Config, is_headless: bool) -> std::io::Result<()> {
+    let pid = instance::read_pid(&config.get_lock_path())
+        .map(|pid| format!(" (pid {})", pid))
+        .unwrap_or_default();
+
+    #[cfg(unix)]
+    {
+        let (request, intent) = if is_headless {
+            (Request::Reload, "reload the schedule")
+        } else {
+            (Request::OpenViewer, "open the viewer")
+        };
+        match ipc::send_request(config.get_socket_path(), &request).await {
+            Ok(response) if response.is_ok() => {
+                println!("wesche is already running{}, asked it to {}", pid, intent);
+                return Ok(());
+            }
+            // e.g. a headless instance has no viewer to open
+            Ok(response) => {
+                eprintln!(
+                    "wesche is already running{}, but couldn't {}: {}",
+                    pid,
+                    intent,
+                    response.get_error().unwrap_or("unknown error")
+                );
+                std::process::exit(1);
+            }
+            Err(err) => {
+                dbg!(err);
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = is_headless;
+
+    eprintln!("wesche is already running{}", pid);
+    std::process::exit(1);
+}
+
+async fn build_system_tray(
+    application_controller: &Arc<Mutex<controller::Application>>,
+) -> Result<systray::Application, systray::Error> {
+    let mut system_tray = systray::Application::new()?;
     {
         {
             let application_controller = application_controller.clone();
-            system_tray
-                .set_icon_from_file(
-                    application_controller
-                        .lock()
-                        .await
-                        .get_icon_file_path()
-                        .as_str(),
-                )
-                .expect("Failed to set icon");
+            system_tray.set_icon_from_file(
+                application_controller
+                    .lock()
+                    .await
+                    .get_icon_file_path()
+                    .as_str(),
+            )?;
         }
         {
             let application_controller = application_controller.clone();
-            system_tray
-                .add_menu_item("Open Task Viewer", move |_| {
+            system_tray.add_menu_item("Open Task Viewer", move |_| {
+                async_std::task::block_on(async {
+                    let mut application_controller = application_controller.lock().await;
+                    application_controller.open_viewer();
+                    Ok::<_, systray::Error>(())
+                })
+            })?;
+        }
+        // without buttons on the notifications, their actions apply to the
+        // last task notification from the tray instead
+        if !application_controller
+            .lock()
+            .await
+            .supports_notification_actions()
+        {
+            for action in controller::TASK_ACTIONS {
+                let application_controller = application_controller.clone();
+                system_tray.add_menu_item(&action.get_label(), move |_| {
                     async_std::task::block_on(async {
-                        let mut application_controller = application_controller.lock().await;
-                        application_controller.open_viewer();
+                        application_controller
+                            .lock()
+                            .await
+                            .request_action(None, action);
                         Ok::<_, systray::Error>(())
                     })
-                })
-                .expect("Failed to add menu item");
+                })?;
+            }
         }
-        {
+        for minutes in controller::DO_NOT_DISTURB_MINUTES {
             let application_controller = application_controller.clone();
-            system_tray
-                .add_menu_item("Quit", move |_| {
+            system_tray.add_menu_item(
+                &format!("Do Not Disturb for {} min", minutes),
+                move |_| {
                     async_std::task::block_on(async {
-                        let mut application_controller = application_controller.lock().await;
-                        application_controller.shutdown();
+                        application_controller
+                            .lock()
+                            .await
+                            .request_do_not_disturb(Some(minutes));
                         Ok::<_, systray::Error>(())
                     })
+                },
+            )?;
+        }
+        {
+            let application_controller = application_controller.clone();
+            system_tray.add_menu_item("Resume Notifications", move |_| {
+                async_std::task::block_on(async {
+                    application_controller
+                        .lock()
+                        .await
+                        .request_do_not_disturb(None);
+                    Ok::<_, systray::Error>(())
+                })
+            })?;
+        }
+        {
+            let application_controller = application_controller.clone();
+            system_tray.add_menu_item("Quit", move |_| {
+                async_std::task::block_on(async {
+                    let mut application_controller = application_controller.lock().await;
+                    application_controller.shutdown();
+                    Ok::<_, systray::Error>(())
                 })
-                .expect("Failed to add menu item");
+            })?;
         }
     }
 
-    async_std::task::spawn(async move {
-        system_tray
-            .wait_for_message()
-            .expect("Failed to wait for sytem_tray message");
-
-        system_tray
-            .shutdown()
-            .expect("Failed to shutdown system tray");
-    });
-
-    file_observer_handle.await;
-    application_loop_handle.await;
-    opening_viewer_handle.await;
-
-    Ok(())
+    Ok(system_tray)
 }